@@ -0,0 +1,135 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::verification;
+
+// The total weight the continuations of each blended pattern are scaled to.
+const SCALE: f32 = 1000.0;
+
+// Blends several rules with weights into one, e.g. for names halfway between two cultures.
+// The continuations of each pattern are the weighted mix of their probabilities in the rules that have the pattern,
+// the alphabets are merged, and the word lengths are the weighted averages. Other sections, such as orthographies,
+// are taken from the rules with the highest weight.
+// weights: the weight of each of the rules, in the same order.
+pub fn blend_rules<'a>(rules: &[&BTreeMap<String, BTreeMap<String, u32>>], weights: &[u32]) -> Result<BTreeMap<String, BTreeMap<String, u32>>, &'a str> {
+    if rules.len() != weights.len() {
+        return Err("Rules and weights don't match.")
+    }
+    // Rules without weight are left out, so wildcards can't pick letters that only they have patterns for.
+    let models: Vec<_> = rules.iter().copied().zip(weights.iter().copied()).filter(|(_, w)| *w > 0).collect();
+    // Sums of weights are u64, since the weights and continuations of several rules can add up to more than a u32.
+    let total: u64 = weights.iter().map(|w| *w as u64).sum();
+    if models.is_empty() || total == 0 {
+        return Err("No rules with weight to blend.")
+    }
+    for (rules, _) in &models {
+        if verification::verify_rules(rules).is_err() {
+            return Err("Rules to blend are not valid.")
+        }
+    }
+
+    // Mix the probabilities of the continuations of every pattern.
+    let mut mixed: BTreeMap<String, BTreeMap<String, f32>> = BTreeMap::new();
+    let mut pattern_weights: BTreeMap<String, u64> = BTreeMap::new();
+    for (rules, weight) in &models {
+        for (pattern, continuations) in rules.iter().filter(|(k, _)| !verification::is_section(k)) {
+            let sum: u64 = continuations.values().map(|w| *w as u64).sum();
+            let entry = mixed.entry(pattern.to_owned()).or_default();
+            for (continuation, w) in continuations {
+                let probability = if sum == 0 { 0.0 } else { *w as f32 / sum as f32 };
+                *entry.entry(continuation.to_owned()).or_default() += probability * *weight as f32;
+            }
+            *pattern_weights.entry(pattern.to_owned()).or_default() += *weight as u64;
+        }
+    }
+
+    // Normalize by the weights of the rules having each pattern, keeping possible continuations possible.
+    let mut result: BTreeMap<String, BTreeMap<String, u32>> = BTreeMap::new();
+    for (pattern, continuations) in mixed {
+        let weight = pattern_weights[&pattern];
+        let continuations = continuations.into_iter().map(|(c, p)| {
+            let scaled = (p / weight as f32 * SCALE).round() as u32;
+            (c, if p > 0.0 { scaled.max(1) } else { 0 })
+        }).collect();
+        result.insert(pattern, continuations);
+    }
+
+    // Merge the alphabets.
+    let alphabet: BTreeSet<char> = models.iter().flat_map(|(rules, _)| rules["alphabet"].first_key_value().unwrap().0.chars()).collect();
+    result.insert("alphabet".to_owned(), BTreeMap::from([(alphabet.into_iter().collect(), 0)]));
+
+    // Average the word lengths, keeping the average between the minimum and maximum.
+    let average = |key: &str| -> u32 {
+        let sum: u64 = models.iter().map(|(rules, w)| rules["word_length"][key] as u64 * *w as u64).sum();
+        (sum as f32 / total as f32).round() as u32
+    };
+    let (min, max) = (average("min"), average("max"));
+    let avg = average("avg").clamp(min, max);
+    result.insert("word_length".to_owned(), BTreeMap::from([
+        ("min".to_owned(), min),
+        ("avg".to_owned(), avg),
+        ("max".to_owned(), max),
+    ]));
+
+    // Take the other sections from the rules with the highest weight.
+    let heaviest = models.iter().max_by_key(|(_, w)| *w).unwrap().0;
+    for (section, entries) in heaviest.iter().filter(|(k, _)| verification::is_section(k)) {
+        result.entry(section.to_owned()).or_insert_with(|| entries.clone());
+    }
+
+    if verification::verify_rules(&result).is_err() {
+        return Err("Blended rules are not valid.")
+    }
+    Ok(result)
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_rules(alphabet: &str, lengths: (u32, u32, u32), patterns: &[(&str, &[(&str, u32)])]) -> BTreeMap<String, BTreeMap<String, u32>> {
+        let mut rules: BTreeMap<String, BTreeMap<String, u32>> = patterns.iter()
+            .map(|(p, c)| (p.to_string(), c.iter().map(|(k, v)| (k.to_string(), *v)).collect()))
+            .collect();
+        rules.insert("alphabet".to_owned(), BTreeMap::from([(alphabet.to_owned(), 0)]));
+        rules.insert("word_length".to_owned(), BTreeMap::from([
+            ("min".to_owned(), lengths.0),
+            ("avg".to_owned(), lengths.1),
+            ("max".to_owned(), lengths.2),
+        ]));
+        rules
+    }
+
+    #[test]
+    fn blend_rules_correct_values() {
+        let first = get_rules("ab", (2, 4, 6), &[(" ", &[("a", 1)]), ("a", &[(" ", 1), ("b", 3)]), ("b", &[(" ", 1)])]);
+        let second = get_rules("ac", (4, 8, 12), &[(" ", &[("c", 1)]), ("a", &[(" ", 1), ("c", 1)]), ("c", &[(" ", 1), ("a", 0)])]);
+        let result = blend_rules(&[&first, &second], &[3, 1]).expect("Failed to blend rules.");
+
+        assert_eq!(result["alphabet"], BTreeMap::from([("abc".to_owned(), 0)]));
+        assert_eq!(result["word_length"], BTreeMap::from([("min".to_owned(), 3), ("avg".to_owned(), 5), ("max".to_owned(), 8)]));
+        assert_eq!(result[" "], BTreeMap::from([("a".to_owned(), 750), ("c".to_owned(), 250)]));
+        assert_eq!(result["a"], BTreeMap::from([(" ".to_owned(), 313), ("b".to_owned(), 563), ("c".to_owned(), 125)]));
+        assert_eq!(result["c"], BTreeMap::from([(" ".to_owned(), 1000), ("a".to_owned(), 0)]));
+    }
+
+    #[test]
+    fn blend_rules_invalid_values() {
+        let rules = get_rules("ab", (2, 4, 6), &[(" ", &[("a", 1)]), ("a", &[(" ", 1)])]);
+        assert!(blend_rules(&[], &[]).is_err());
+        assert!(blend_rules(&[&rules], &[0]).is_err());
+        assert!(blend_rules(&[&rules], &[1, 1]).is_err());
+        assert!(blend_rules(&[&BTreeMap::new()], &[1]).is_err());
+    }
+
+    #[test]
+    fn blend_rules_large_weights() {
+        let first = get_rules("ab", (2, 4, 6), &[(" ", &[("a", u32::MAX)]), ("a", &[(" ", u32::MAX), ("b", u32::MAX)]), ("b", &[(" ", 1)])]);
+        let second = get_rules("ab", (4, 8, 12), &[(" ", &[("b", 1)]), ("a", &[(" ", 1)]), ("b", &[(" ", 1)])]);
+        let result = blend_rules(&[&first, &second], &[4_000_000_000, 4_000_000_000]).expect("Failed to blend rules.");
+        assert_eq!(result["word_length"], BTreeMap::from([("min".to_owned(), 3), ("avg".to_owned(), 6), ("max".to_owned(), 9)]));
+        assert_eq!(result[" "], BTreeMap::from([("a".to_owned(), 500), ("b".to_owned(), 500)]));
+        assert_eq!(result["a"], BTreeMap::from([(" ".to_owned(), 750), ("b".to_owned(), 250)]));
+    }
+}
+// TESTS END
@@ -0,0 +1,160 @@
+use std::collections::{btree_map::Entry::Vacant, BTreeMap};
+
+// Characters that are vowels unless the rules say otherwise.
+const DEFAULT_VOWELS: &str = "aeiouyáéíóúýàèìòùâêîôûäëïöüÿåæøœãõɑɒɐɔəɚɛɜɝɪɨʊʉʌɯɤɘɵɞɶ";
+
+// The sonority of consonants unless the rules say otherwise, from stops (1) to glides (5). Vowels have a sonority of 6.
+const DEFAULT_SONORITY: [(&str, u32); 5] = [
+    ("pbtdkgqcʔɡɖʈɟ", 1),
+    ("fvszʃʒθðxhɣχçʁħʕɸβ", 2),
+    ("mnŋɲɳɱ", 3),
+    ("lrɹɾʀɭʎɽɫ", 4),
+    ("jwɥʋɰ", 5),
+];
+
+// The sonority of vowels.
+pub const VOWEL_SONORITY: u32 = 6;
+
+// The classes of a language, such as vowels (V) and consonants (C), along with the sonority of each letter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Classes {
+    classes: BTreeMap<char, Vec<String>>,
+    sonority: BTreeMap<char, u32>,
+}
+
+impl Classes {
+    // Creates the classes from the classes and sonority sections of the rules.
+    // Each class is written as "N=mn: 0", where N is the name of the class and the rest are its members.
+    // Members with several characters are separated by spaces, e.g. "N=m n ng: 0".
+    // If V (vowels) or C (consonants) are not defined, they are derived from the alphabet.
+    pub fn from_rules<'a>(rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Result<Self, &'a str> {
+        let alphabet = match rules.get("alphabet").and_then(|a| a.first_key_value()) {
+            Some((k, _)) => k.to_owned(),
+            None => return Err("No alphabet in rules."),
+        };
+
+        let mut classes = BTreeMap::new();
+        for entry in rules.get("classes").into_iter().flat_map(|c| c.keys()) {
+            let (name, members) = parse_class(entry)?;
+            classes.insert(name, members);
+        }
+
+        let mut sonority = BTreeMap::new();
+        for (letter, value) in rules.get("sonority").into_iter().flatten() {
+            let mut chars = letter.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => sonority.insert(c, *value),
+                _ => return Err("Sonority is not defined for a single letter."),
+            };
+        }
+
+        Ok(Self::build(&alphabet, classes, sonority))
+    }
+
+    // Creates the default classes for an alphabet, e.g. for words of a sample text.
+    pub fn from_alphabet(alphabet: &str) -> Self {
+        Self::build(alphabet, BTreeMap::new(), BTreeMap::new())
+    }
+
+    // Fills in the vowels, consonants and sonorities that were not defined.
+    fn build(alphabet: &str, mut classes: BTreeMap<char, Vec<String>>, mut sonority: BTreeMap<char, u32>) -> Self {
+        classes.entry('V').or_insert_with(|| {
+            alphabet.chars().filter(|c| DEFAULT_VOWELS.contains(*c)).map(|c| c.to_string()).collect()
+        });
+        if !classes.contains_key(&'C') {
+            let vowels = &classes[&'V'];
+            let consonants = alphabet.chars().map(|c| c.to_string()).filter(|c| !vowels.contains(c)).collect();
+            classes.insert('C', consonants);
+        }
+
+        // Vowels always have the highest sonority, and unknown consonants the lowest.
+        for c in alphabet.chars() {
+            if classes[&'V'].contains(&c.to_string()) {
+                sonority.insert(c, VOWEL_SONORITY);
+            } else if let Vacant(e) = sonority.entry(c) {
+                let value = DEFAULT_SONORITY.iter().find(|(letters, _)| letters.contains(c)).map_or(1, |(_, v)| *v);
+                e.insert(value);
+            }
+        }
+
+        Self { classes, sonority }
+    }
+
+    // Gets the members of a class.
+    pub fn get(&self, name: char) -> Option<&Vec<String>> {
+        self.classes.get(&name)
+    }
+
+    // Gets the names of all classes.
+    pub fn names(&self) -> impl Iterator<Item = &char> {
+        self.classes.keys()
+    }
+
+    // Whether a letter is a vowel.
+    pub fn is_vowel(&self, c: char) -> bool {
+        self.sonority(c) == VOWEL_SONORITY
+    }
+
+    // Gets the sonority of a letter. Letters outside the alphabet are treated as vowels if they look like one.
+    pub fn sonority(&self, c: char) -> u32 {
+        match self.sonority.get(&c) {
+            Some(s) => *s,
+            None => if DEFAULT_VOWELS.contains(c) { VOWEL_SONORITY } else { 1 },
+        }
+    }
+}
+
+// Parses a class entry such as "V=aeiou" into its name and members.
+fn parse_class<'a>(entry: &str) -> Result<(char, Vec<String>), &'a str> {
+    let (name, members) = entry.split_once('=').ok_or("Class is not in the form \"N=members\".")?;
+    let mut chars = name.trim().chars();
+    let name = match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_uppercase() => c,
+        _ => return Err("Class name is not a single uppercase letter."),
+    };
+
+    let members: Vec<String> = if members.trim().contains(char::is_whitespace) {
+        members.split_whitespace().map(|m| m.to_owned()).collect()
+    } else {
+        members.trim().chars().map(|c| c.to_string()).collect()
+    };
+    if members.is_empty() {
+        return Err("Class has no members.")
+    }
+    Ok((name, members))
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_class_correct_values() {
+        assert_eq!(parse_class("V=aei"), Ok(('V', vec!["a".to_owned(), "e".to_owned(), "i".to_owned()])));
+        assert_eq!(parse_class("N = m ng"), Ok(('N', vec!["m".to_owned(), "ng".to_owned()])));
+        assert!(parse_class("v=aei").is_err());
+        assert!(parse_class("VV=aei").is_err());
+        assert!(parse_class("V=").is_err());
+        assert!(parse_class("V aei").is_err());
+    }
+
+    #[test]
+    fn from_rules_defaults() {
+        let rules = BTreeMap::from([
+            ("alphabet".to_owned(), BTreeMap::from([("abdlr".to_owned(), 0)])),
+            ("classes".to_owned(), BTreeMap::from([("L=lr".to_owned(), 0)])),
+            ("sonority".to_owned(), BTreeMap::from([("d".to_owned(), 2)])),
+        ]);
+        let classes = Classes::from_rules(&rules).expect("Failed to parse classes.");
+        assert_eq!(classes.get('V'), Some(&vec!["a".to_owned()]));
+        assert_eq!(classes.get('C').map(|c| c.concat()), Some("bdlr".to_owned()));
+        assert_eq!(classes.get('L').map(|c| c.concat()), Some("lr".to_owned()));
+        assert_eq!(classes.sonority('b'), 1);
+        assert_eq!(classes.sonority('d'), 2);
+        assert_eq!(classes.sonority('l'), 4);
+        assert!(classes.is_vowel('a'));
+        assert!(!classes.is_vowel('r'));
+    }
+}
+// TESTS END
@@ -1,29 +1,116 @@
 use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command};
+use rand::SeedableRng;
 
-use crate::reader;
+use crate::classes::Classes;
+use crate::family::{self, Daughter, Family};
+use crate::output::OutputFormat;
+use crate::paths::Resolver;
+use crate::phonology::{Phonology, Preset};
+use crate::provenance::{self, Provenance};
+use crate::rng::Pcg32;
+use crate::template::Combination;
+use crate::{extraction, nativization, pronunciation, reader, seed, sound_change};
+
+// The kinds of files the rules can be read from.
+#[derive(PartialEq)]
+enum Source {
+    Rules,
+    Sample,
+    Dictionary,
+}
 
 // Returns the map for the rules to be used when generating the language.
-// save_rules: if true, saves these rules in './assets/local/rules.yaml', otherwise, does not save them.
-pub fn get_rules(save_rules: bool) -> Result<BTreeMap<String, BTreeMap<String, u32>>, Box<dyn std::error::Error>> {
-    // Tuple containing the file path and whether the file contains sample text or not.
-    let path = get_path();
-
-    // If the path was given as a sample text, instead of rules.
-    if path.1 {
-        // Generate rules based on the sample text.
-        let text = fs::read_to_string(path.0)?;
-        let rules = reader::rules_from_string(&text, 3);
-
-        // Save the rules generated by the reader.
-        if save_rules {
-            let path = "./assets/local/rules.yaml";
-            let writer = fs::File::create(path)?;
-            serde_yaml::to_writer(writer, &rules)?;
+// Rules learned from a sample text or pronunciation dictionary are also written to the output, if given as application argument.
+pub fn get_rules(matches: &ArgMatches, resolver: &Resolver) -> Result<BTreeMap<String, BTreeMap<String, u32>>, Box<dyn std::error::Error>> {
+    let cmd = generation_matches(matches);
+    let rules = rules_from_matches(cmd, resolver)?;
+
+    // Write the rules generated by the reader.
+    if get_path(cmd, resolver)?.1 != Source::Rules {
+        if let Some(output) = cmd.get_one::<String>("output") {
+            write_rules(cmd, resolver, &rules, Some(output))?;
         }
+    }
+
+    Ok(rules)
+}
+
+// Learns the rules from the sample text or pronunciation dictionary and writes them to the output,
+// or prints them out if no output was given. Returns whether the learn command was given.
+pub fn learn(matches: &ArgMatches, resolver: &Resolver) -> Result<bool, Box<dyn std::error::Error>> {
+    let Some(cmd) = matches.subcommand_matches("learn") else { return Ok(false) };
+
+    let rules = rules_from_matches(cmd, resolver)?;
+    write_rules(cmd, resolver, &rules, cmd.get_one::<String>("output").map(|o| o.as_str()))?;
+    Ok(true)
+}
+
+// Writes learned rules, along with where they were learned from, to a file, creating its directory if needed,
+// or prints them out if the output is None or "-". Refuses to overwrite an existing file unless forced.
+fn write_rules(cmd: &ArgMatches, resolver: &Resolver, rules: &BTreeMap<String, BTreeMap<String, u32>>, output: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let (path, _, _) = get_path(cmd, resolver)?;
+    let contents = fs::read(&path)?;
+    let source = Path::new(&path).file_name().map_or(path.clone(), |n| n.to_string_lossy().into_owned());
+    let depth = *cmd.get_one::<u32>("depth").unwrap() as usize;
+    let yaml = Provenance::new(&source, &contents, depth).annotate(&serde_yaml::to_string(rules)?);
+    write_yaml(&yaml, output, cmd.get_flag("force"))
+}
+
+// Writes new rules, e.g. of an invented language or a blend, to a file like write_yaml, along with how they were made
+// (e.g. "Invented") and the seed they were generated from if any.
+pub fn write_new_rules(rules: &BTreeMap<String, BTreeMap<String, u32>>, origin: &str, seed: Option<u64>, output: Option<&str>, force: bool) -> Result<(), Box<dyn std::error::Error>> {
+    write_yaml(&provenance::annotate_new(&serde_yaml::to_string(rules)?, origin, seed), output, force)
+}
+
+// Writes the yaml of rules to a file, creating its directory if needed, or prints it out if the output is None or "-".
+// Refuses to overwrite an existing file unless forced.
+fn write_yaml(yaml: &str, output: Option<&str>, force: bool) -> Result<(), Box<dyn std::error::Error>> {
+    match output {
+        None | Some("-") => print!("{yaml}"),
+        Some(output) => {
+            if Path::new(output).exists() && !force {
+                return Err(format!("\"{output}\" already exists, use --force to overwrite it.").into())
+            }
+            save_text(yaml, output)?;
+            eprintln!("Saved rules in {output}");
+        }
+    }
+    Ok(())
+}
+
+// Reads or learns the rules from the file given as application argument.
+fn rules_from_matches(cmd: &ArgMatches, resolver: &Resolver) -> Result<BTreeMap<String, BTreeMap<String, u32>>, Box<dyn std::error::Error>> {
+    // Tuple containing the file path, the kind of file, and the format of the sample text.
+    let path = get_path(cmd, resolver)?;
+    let depth = *cmd.get_one::<u32>("depth").unwrap() as usize;
+
+    // If the path was given as a sample text or pronunciation dictionary, instead of rules.
+    if path.1 != Source::Rules {
+        let rules = if path.1 == Source::Sample {
+            // Extract the prose from the sample text, detecting its format unless it was given.
+            let bytes = fs::read(&path.0)?;
+            let format = match path.2 {
+                Some(f) => f,
+                None => extraction::detect_format(&path.0, &bytes),
+            };
+            let text = extraction::extract_text(&bytes, format)?;
+
+            // Report the harmony tendencies of the sample text if wanted.
+            if cmd.get_flag("harmony") {
+                report_harmony(&text);
+            }
+
+            // Generate rules based on the sample text.
+            reader::rules_from_string(&text, depth)
+        } else {
+            // Generate rules and spellings based on the pronunciations in the dictionary.
+            let text = fs::read_to_string(&path.0)?;
+            pronunciation::rules_from_dictionary(&text, depth)?
+        };
 
         return Ok(rules)
     }
@@ -35,19 +122,307 @@ pub fn get_rules(save_rules: bool) -> Result<BTreeMap<String, BTreeMap<String, u
     Ok(rules)
 }
 
-// Gets the path given as application argument and completes it if it only was the file name (not full path).
-// Returns full path tupled with boolean for whether it was given as a sample text or not (given as path to rules instead).
-fn get_path() -> (String, bool) {
-    // Define command for file path.
-    let cmd = Command::new("configuration")
+// Gets the path given as application argument and resolves it if it only was the file name (not full path).
+// Returns the resolved path tupled with the kind of file it was given as (sample text, pronunciation dictionary or rules),
+// and the format of the sample text if it was given.
+fn get_path(cmd: &ArgMatches, resolver: &Resolver) -> Result<(String, Source, Option<extraction::Format>), Box<dyn std::error::Error>> {
+    // Extract path from cmd line arg. Commands that only learn rules have no rules argument.
+    let rules = cmd.try_get_one::<String>("rules").ok().flatten();
+    let path = cmd.get_one::<String>("sample")
+        .or(cmd.get_one::<String>("dictionary"))
+        .or(rules)
+        .map_or("", |p| p.as_str());
+
+    // Search for the file if needed.
+    let path = resolver.resolve(path)?.to_string_lossy().into_owned();
+    let format = cmd.get_one::<String>("sample_format").and_then(|f| extraction::Format::from_name(f));
+    let source = if cmd.contains_id("sample") {
+        Source::Sample
+    } else if cmd.contains_id("dictionary") {
+        Source::Dictionary
+    } else {
+        Source::Rules
+    };
+    Ok((path, source, format))
+}
+
+// Gets the resolver for files given by name, searching the directories given as application arguments first.
+pub fn resolver(matches: &ArgMatches) -> Resolver {
+    let directories: Vec<PathBuf> = matches.get_many::<String>("search_path").into_iter().flatten().map(PathBuf::from).collect();
+    Resolver::from_environment(&directories)
+}
+
+// Gets the matches of the generate command, or of the application if no command was given.
+fn generation_matches(matches: &ArgMatches) -> &ArgMatches {
+    matches.subcommand_matches("generate").unwrap_or(matches)
+}
+
+// Gets the number of words to generate.
+pub fn get_count(matches: &ArgMatches) -> u32 {
+    *generation_matches(matches).get_one::<u32>("count").unwrap()
+}
+
+// Gets the seed of the rng, if given as application argument, either as a number or as a passphrase.
+pub fn get_seed(matches: &ArgMatches) -> Option<u64> {
+    match matches.get_one::<String>("seed_phrase") {
+        Some(phrase) => Some(seed::seed_from_phrase(phrase)),
+        None => matches.get_one::<u64>("seed").copied(),
+    }
+}
+
+// Gets the path in the tree of seeds of the seed to use, if given as application argument.
+pub fn get_seed_path(matches: &ArgMatches) -> Option<String> {
+    matches.get_one::<String>("seed_path").cloned()
+}
+
+// Gets the paths of the rules files to verify, if the verify command was given.
+pub fn get_verify_paths(matches: &ArgMatches) -> Option<Vec<String>> {
+    let cmd = matches.subcommand_matches("verify")?;
+    Some(cmd.get_many::<String>("models")?.cloned().collect())
+}
+
+// Gets the path of the rules and the words to score, if the score command was given.
+pub fn get_score(matches: &ArgMatches) -> Option<(String, Vec<String>)> {
+    let cmd = matches.subcommand_matches("score")?;
+    Some((cmd.get_one::<String>("rules")?.to_owned(), cmd.get_many::<String>("words")?.cloned().collect()))
+}
+
+// Gets the path of the rules, the keys to name, the salt and whether the names should be unique, if the name command was given.
+pub fn get_naming(matches: &ArgMatches) -> Option<(String, Vec<String>, String, bool)> {
+    let cmd = matches.subcommand_matches("name")?;
+    Some((
+        cmd.get_one::<String>("rules")?.to_owned(),
+        cmd.get_many::<String>("keys")?.cloned().collect(),
+        cmd.get_one::<String>("salt")?.to_owned(),
+        cmd.get_flag("unique"),
+    ))
+}
+
+// Gets the path of the rules and the number of words to generate for their statistics, if the stats command was given.
+pub fn get_statistics(matches: &ArgMatches) -> Option<(String, u32)> {
+    let cmd = matches.subcommand_matches("stats")?;
+    Some((cmd.get_one::<String>("rules")?.to_owned(), *cmd.get_one::<u32>("count")?))
+}
+
+// Gets the path of the sample text to convert, its format if given and the file to save the plain text in if given,
+// if the convert command was given.
+pub fn get_conversion(matches: &ArgMatches) -> Option<(String, Option<String>, Option<String>)> {
+    let cmd = matches.subcommand_matches("convert")?;
+    Some((
+        cmd.get_one::<String>("sample")?.to_owned(),
+        cmd.get_one::<String>("sample_format").cloned(),
+        cmd.get_one::<String>("output").cloned(),
+    ))
+}
+
+// Extracts the prose of a sample text as plain text, detecting its format unless it was given.
+pub fn convert_sample(path: &str, format: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+    let bytes = fs::read(path)?;
+    let format = match format.and_then(extraction::Format::from_name) {
+        Some(f) => f,
+        None => extraction::detect_format(path, &bytes),
+    };
+    extraction::extract_text(&bytes, format)
+}
+
+// Gets the paths of the sound changes, the words and the rules, if the change command was given.
+pub fn get_change_paths(matches: &ArgMatches) -> Option<(String, String, Option<String>)> {
+    let cmd = matches.subcommand_matches("change")?;
+    Some((
+        cmd.get_one::<String>("changes")?.to_owned(),
+        cmd.get_one::<String>("words")?.to_owned(),
+        cmd.get_one::<String>("rules").cloned(),
+    ))
+}
+
+// Applies the sound changes of a file to the words of another, returning each word tupled with its changed form.
+// The classes used by the sound changes are read from the rules if given, otherwise derived from the letters of the words.
+pub fn change_words(changes: &str, words: &str, rules: Option<&str>, resolver: &Resolver) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let words = fs::read_to_string(words)?;
+    let words: Vec<&str> = words.split_whitespace().collect();
+    let classes = match rules {
+        Some(path) => {
+            Classes::from_rules(&read_rules(path, resolver)?)?
+        }
+        None => Classes::from_alphabet(&words.concat()),
+    };
+
+    let changes = fs::read_to_string(changes)?;
+    let changes = sound_change::parse_changes(&changes, &classes)?;
+    Ok(sound_change::apply_changes(&words, &changes))
+}
+
+// Gets the paths of the foreign words and the rules, if the nativize command was given.
+pub fn get_nativize_paths(matches: &ArgMatches) -> Option<(String, String)> {
+    let cmd = matches.subcommand_matches("nativize")?;
+    Some((
+        cmd.get_one::<String>("words")?.to_owned(),
+        cmd.get_one::<String>("rules")?.to_owned(),
+    ))
+}
+
+// Adapts the foreign words of a file to the language of the rules, returning each word tupled with its native form.
+pub fn nativize_words(words: &str, rules: &str, resolver: &Resolver) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let words = fs::read_to_string(words)?;
+    let words: Vec<&str> = words.split_whitespace().collect();
+    let rules = read_rules(rules, resolver)?;
+    Ok(nativization::nativize_words(&words, &rules)?)
+}
+
+// Generates a language family, if the family command was given, and saves the rules of each daughter language
+// as "<name>.yaml" in the output directory if given, otherwise prints them out as yaml documents named after the daughters.
+// Refuses to overwrite any existing file unless forced, before saving any of them.
+pub fn get_family(matches: &ArgMatches, resolver: &Resolver, seed: u64) -> Result<Option<Family>, Box<dyn std::error::Error>> {
+    let Some(cmd) = matches.subcommand_matches("family") else { return Ok(None) };
+
+    let rules = read_rules(cmd.get_one::<String>("rules").unwrap(), resolver)?;
+    let amount = *cmd.get_one::<u32>("words").unwrap();
+    let daughters = *cmd.get_one::<usize>("daughters").unwrap();
+    let changes = *cmd.get_one::<usize>("changes").unwrap();
+    let family = family::generate_family(&mut Pcg32::seed_from_u64(seed), amount, daughters, changes, &rules)?;
+    let yaml = |daughter: &Daughter| -> Result<String, serde_yaml::Error> {
+        Ok(provenance::annotate_new(&serde_yaml::to_string(&daughter.rules)?, &format!("Derived as {}", daughter.name), Some(seed)))
+    };
+
+    // Save or print out the rules of the daughter languages.
+    let Some(output) = cmd.get_one::<String>("output").map(Path::new) else {
+        for daughter in &family.daughters {
+            print!("---\n{}", yaml(daughter)?);
+        }
+        return Ok(Some(family))
+    };
+    let paths: Vec<PathBuf> = family.daughters.iter().map(|d| output.join(format!("{}.yaml", d.name))).collect();
+    if let Some(existing) = paths.iter().find(|p| p.exists()) {
+        if !cmd.get_flag("force") {
+            return Err(format!("\"{}\" already exists, use --force to overwrite it.", existing.display()).into())
+        }
+    }
+    for (daughter, path) in family.daughters.iter().zip(&paths) {
+        save_text(&yaml(daughter)?, &path.to_string_lossy())?;
+    }
+
+    Ok(Some(family))
+}
+
+// Gets the knobs of a new language, if the invent command was given.
+pub fn get_phonology(matches: &ArgMatches) -> Option<Phonology> {
+    let cmd = matches.subcommand_matches("invent")?;
+
+    // The knobs of the preset can be changed one by one.
+    let mut phonology = Phonology::from_preset(Preset::from_name(cmd.get_one::<String>("preset")?)?);
+    if let Some(c) = cmd.get_one::<usize>("consonants") { phonology.consonants = *c }
+    if let Some(v) = cmd.get_one::<usize>("vowels") { phonology.vowels = *v }
+    if let Some(c) = cmd.get_one::<usize>("complexity") { phonology.complexity = *c }
+
+    Some(phonology)
+}
+
+// Gets the rules files to blend tupled with their weights, if the blend command was given. Each rules file can be followed by its weight, e.g. "elvish.yaml:3", and weighs 1 otherwise.
+pub fn get_blend(matches: &ArgMatches) -> Option<Vec<(String, u32)>> {
+    let cmd = matches.subcommand_matches("blend")?;
+    let models = cmd.get_many::<String>("models")?.map(|m| match m.rsplit_once(':') {
+        Some((path, weight)) if weight.parse::<u32>().is_ok() => (path.to_owned(), weight.parse().unwrap()),
+        _ => (m.to_owned(), 1),
+    }).collect();
+    Some(models)
+}
+
+// Gets the file to save the rules of the invent or blend command in, if given, and whether to overwrite it.
+pub fn get_new_rules_output(matches: &ArgMatches) -> (Option<String>, bool) {
+    let cmd = matches.subcommand_matches("invent").or(matches.subcommand_matches("blend"));
+    match cmd {
+        Some(cmd) => (cmd.get_one::<String>("output").cloned(), cmd.get_flag("force")),
+        None => (None, false),
+    }
+}
+
+// Gets the rules files to identify the language with and the text to identify, if the identify command was given.
+pub fn get_identification(matches: &ArgMatches) -> Option<(Vec<String>, String)> {
+    let cmd = matches.subcommand_matches("identify")?;
+    let models = cmd.get_many::<String>("models")?.cloned().collect();
+    Some((models, cmd.get_one::<String>("text")?.to_owned()))
+}
+
+// Gets the paths of the two rules files to compare, if the compare command was given.
+pub fn get_compare_paths(matches: &ArgMatches) -> Option<(String, String)> {
+    let cmd = matches.subcommand_matches("compare")?;
+    Some((cmd.get_one::<String>("first")?.to_owned(), cmd.get_one::<String>("second")?.to_owned()))
+}
+
+// Reads the rules from a yaml file, searching for it with the resolver if it only was the file name.
+pub fn read_rules(path: &str, resolver: &Resolver) -> Result<BTreeMap<String, BTreeMap<String, u32>>, Box<dyn std::error::Error>> {
+    Ok(serde_yaml::from_reader(fs::File::open(resolver.resolve(path)?)?)?)
+}
+
+// Saves text in a file, creating its directory if needed.
+pub fn save_text(text: &str, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, text)?;
+    Ok(())
+}
+
+// Prints out the pairs of letters that appear in the same word less often than by chance, and the harmony systems they suggest.
+fn report_harmony(text: &str) {
+    let (tendencies, systems) = reader::harmony_from_string(text);
+    println!("Harmony tendencies (how often letters appear in the same word compared to chance):");
+    for t in tendencies.iter().take(10) {
+        println!("  {} {}: {:.2}", t.first, t.second, t.ratio);
+    }
+    if systems.is_empty() {
+        println!("No harmony detected.");
+    }
+    for system in systems {
+        println!("Suggested harmony: \"{system}\": 0");
+    }
+}
+
+// Gets the number of syllables to aim for, if given as application argument, and whether to hyphenate the words.
+pub fn get_syllables(matches: &ArgMatches) -> (Option<usize>, bool) {
+    let cmd = generation_matches(matches);
+    (cmd.get_one::<usize>("syllables").copied(), cmd.get_flag("hyphenate"))
+}
+
+// Gets whether to print how each word was generated instead of the words.
+pub fn get_explain(matches: &ArgMatches) -> bool {
+    generation_matches(matches).get_flag("explain")
+}
+
+// Gets the format the generated words should be printed in.
+pub fn get_output_format(matches: &ArgMatches) -> OutputFormat {
+    let cmd = generation_matches(matches);
+    cmd.get_one::<String>("format").and_then(|f| OutputFormat::from_name(f)).unwrap_or(OutputFormat::Plain)
+}
+
+// Gets the index of the first word to generate, if the words should be generated by index.
+pub fn get_index(matches: &ArgMatches) -> Option<u64> {
+    generation_matches(matches).get_one::<u64>("index").copied()
+}
+
+// Gets how to combine words generated from the syllable templates of the rules with its patterns,
+// if the words should be generated from templates.
+pub fn get_templates(matches: &ArgMatches) -> Option<Combination> {
+    let cmd = generation_matches(matches);
+    cmd.get_one::<String>("templates").and_then(|t| Combination::from_name(t))
+}
+
+// Adds the arguments for where the rules are read or learned from to a command.
+fn source_args(command: Command) -> Command {
+    command
     .arg(
         Arg::new("rules")
             .long("language-rules")
             .short('r')
             .value_name("YAML_FILE")
             .help("Sets the source file for language rules.")
-            .required(false),
+            .required(false)
     )
+}
+
+// Adds the arguments for learning the rules from a sample text or pronunciation dictionary to a command.
+fn sample_args(command: Command) -> Command {
+    command
     .arg(
         Arg::new("sample")
             .long("sample-text")
@@ -56,23 +431,485 @@ fn get_path() -> (String, bool) {
             .help("Sets the source file for sample text.")
             .required(false)
     )
+    .arg(
+        Arg::new("dictionary")
+            .long("pronunciation-dictionary")
+            .short('p')
+            .value_name("DICT_FILE")
+            .help("Sets the source file for a pronunciation dictionary, in CMUdict or \"word<TAB>/ipa/\" format.")
+            .required(false)
+    )
+    .arg(
+        Arg::new("sample_format")
+            .long("sample-format")
+            .short('f')
+            .value_name("FORMAT")
+            .help("Sets the format of the sample text: plain, html, markdown or epub. Detected from the file if not set.")
+            .value_parser(["plain", "html", "markdown", "md", "epub"])
+            .required(false)
+    )
+    .arg(
+        Arg::new("depth")
+            .long("depth")
+            .short('d')
+            .value_name("LETTERS")
+            .help("Sets the longest pattern, in letters, learned from a sample text or pronunciation dictionary.")
+            .value_parser(clap::value_parser!(u32).range(1..))
+            .default_value("3")
+    )
+    .arg(
+        Arg::new("harmony")
+            .long("harmony")
+            .help("Reports the vowel and consonant harmony tendencies of the sample text.")
+            .action(ArgAction::SetTrue)
+    )
+    .arg(
+        Arg::new("output")
+            .long("output")
+            .short('o')
+            .value_name("YAML_FILE")
+            .help("Sets the file the learned rules are written to, or - to print them out.")
+            .required(false)
+    )
+    .arg(
+        Arg::new("force")
+            .long("force")
+            .help("Overwrites the file the learned rules are written to if it already exists.")
+            .action(ArgAction::SetTrue)
+    )
+}
+
+// Adds the arguments for how the words are generated to a command.
+fn generation_args(command: Command) -> Command {
+    command
+    .arg(
+        Arg::new("count")
+            .long("count")
+            .short('n')
+            .value_name("COUNT")
+            .help("Sets the number of words to generate.")
+            .value_parser(clap::value_parser!(u32))
+            .default_value("10")
+    )
+    .arg(
+        Arg::new("syllables")
+            .long("syllables")
+            .short('y')
+            .value_name("COUNT")
+            .help("Sets the number of syllables the words should have, instead of aiming for the average word length.")
+            .value_parser(clap::value_parser!(usize))
+            .required(false)
+    )
+    .arg(
+        Arg::new("hyphenate")
+            .long("hyphenate")
+            .help("Hyphenates the words into syllables and counts them.")
+            .action(ArgAction::SetTrue)
+    )
+    .arg(
+        Arg::new("templates")
+            .long("templates")
+            .short('t')
+            .value_name("COMBINATION")
+            .help("Generates the words from the syllable templates of the rules, combined with its patterns: none, filter or score.")
+            .value_parser(["none", "filter", "score"])
+            .num_args(0..=1)
+            .default_missing_value("none")
+            .required(false)
+    )
+    .arg(
+        Arg::new("explain")
+            .long("explain")
+            .help("Prints how each word was generated: every step, the random draws, the continuations picked and the candidates.")
+            .conflicts_with_all(["templates", "format"])
+            .action(ArgAction::SetTrue)
+    )
+    .arg(
+        Arg::new("format")
+            .long("format")
+            .value_name("FORMAT")
+            .help("Sets the format the words are printed in: plain, or records of each word generated by index in jsonl, csv or tsv.")
+            .value_parser(["plain", "jsonl", "csv", "tsv"])
+            .conflicts_with("templates")
+            .default_value("plain")
+    )
+    .arg(
+        Arg::new("index")
+            .long("index")
+            .short('i')
+            .value_name("INDEX")
+            .help("Generates the words from the index of the words for the seed, each from its own rng, e.g. word 5000 without generating the ones before it.")
+            .value_parser(clap::value_parser!(u64))
+            .conflicts_with("templates")
+            .required(false)
+    )
+}
+
+// Defines the command for the application arguments. Without a subcommand, the words are generated like with the generate command.
+pub fn cli() -> Command {
+    generation_args(sample_args(source_args(Command::new("configuration"))))
+    .arg(
+        Arg::new("search_path")
+            .long("search-path")
+            .value_name("DIRECTORY")
+            .help("Adds a directory to search for files given by name, before the ones in WORD_GEN_PATH, the config directory and the examples.")
+            .action(ArgAction::Append)
+            .global(true)
+    )
+    .arg(
+        Arg::new("seed")
+            .long("seed")
+            .value_name("SEED")
+            .help("Sets the seed of the rng, instead of a random one, so the same words can be generated again.")
+            .value_parser(clap::value_parser!(u64))
+            .global(true)
+    )
+    .arg(
+        Arg::new("seed_phrase")
+            .long("seed-phrase")
+            .value_name("TEXT")
+            .help("Sets the seed of the rng from a passphrase, e.g. the name of a world.")
+            .conflicts_with("seed")
+            .global(true)
+    )
+    .arg(
+        Arg::new("seed_path")
+            .long("seed-path")
+            .value_name("PATH")
+            .help("Uses the seed at a path of labels in the tree of seeds derived from the seed, e.g. \"world/north/town-3\".")
+            .global(true)
+    )
+    .subcommand(
+        generation_args(sample_args(source_args(Command::new("generate"))))
+            .about("Generates words from the rules read or learned from a file.")
+    )
+    .subcommand(
+        sample_args(Command::new("learn"))
+            .about("Learns the rules from a sample text or pronunciation dictionary and saves them.")
+            .group(ArgGroup::new("source").args(["sample", "dictionary"]).required(true))
+    )
+    .subcommand(
+        Command::new("verify")
+            .about("Verifies rules files and prints whether each is valid.")
+            .arg(
+                Arg::new("models")
+                    .value_name("YAML_FILE")
+                    .help("Sets the rules files to verify.")
+                    .num_args(1..)
+                    .required(true)
+            )
+    )
+    .subcommand(
+        Command::new("score")
+            .about("Scores how likely the language of the rules is to generate each word.")
+            .arg(
+                Arg::new("rules")
+                    .long("language-rules")
+                    .short('r')
+                    .value_name("YAML_FILE")
+                    .help("Sets the language rules the words are scored with.")
+                    .required(true)
+            )
+            .arg(
+                Arg::new("words")
+                    .value_name("WORD")
+                    .help("Sets the words to score.")
+                    .num_args(1..)
+                    .required(true)
+            )
+    )
+    .subcommand(
+        Command::new("name")
+            .about("Names each key, e.g. \"npc:1234\", with the word it always gets from the rules and salt.")
+            .arg(
+                Arg::new("rules")
+                    .long("language-rules")
+                    .short('r')
+                    .value_name("YAML_FILE")
+                    .help("Sets the language rules the names are generated with.")
+                    .required(true)
+            )
+            .arg(
+                Arg::new("keys")
+                    .value_name("KEY")
+                    .help("Sets the keys to name.")
+                    .num_args(1..)
+                    .required(true)
+            )
+            .arg(
+                Arg::new("salt")
+                    .long("salt")
+                    .value_name("SALT")
+                    .help("Sets the salt that gives the same keys other names, e.g. the name of a world.")
+                    .default_value("")
+            )
+            .arg(
+                Arg::new("unique")
+                    .long("unique")
+                    .short('u')
+                    .help("Gives each key a name that isn't the name of a key before it.")
+                    .action(ArgAction::SetTrue)
+            )
+    )
+    .subcommand(
+        Command::new("stats")
+            .about("Prints statistics of the rules and of a sample of words generated with them.")
+            .arg(
+                Arg::new("rules")
+                    .long("language-rules")
+                    .short('r')
+                    .value_name("YAML_FILE")
+                    .help("Sets the language rules.")
+                    .required(true)
+            )
+            .arg(
+                Arg::new("count")
+                    .long("count")
+                    .short('n')
+                    .value_name("COUNT")
+                    .help("Sets the number of words generated for the statistics of the sample.")
+                    .value_parser(clap::value_parser!(u32))
+                    .default_value("1000")
+            )
+    )
+    .subcommand(
+        Command::new("convert")
+            .about("Extracts the prose of a sample text in html, markdown or epub as plain text.")
+            .arg(
+                Arg::new("sample")
+                    .value_name("FILE")
+                    .help("Sets the sample text to convert.")
+                    .required(true)
+            )
+            .arg(
+                Arg::new("sample_format")
+                    .long("sample-format")
+                    .short('f')
+                    .value_name("FORMAT")
+                    .help("Sets the format of the sample text: plain, html, markdown or epub. Detected from the file if not set.")
+                    .value_parser(["plain", "html", "markdown", "md", "epub"])
+                    .required(false)
+            )
+            .arg(
+                Arg::new("output")
+                    .long("output")
+                    .short('o')
+                    .value_name("TXT_FILE")
+                    .help("Sets the file the plain text is saved in, instead of printing it.")
+                    .required(false)
+            )
+    )
+    .subcommand(
+        Command::new("change")
+            .about("Applies sound changes to a list of words and prints each word before and after.")
+            .arg(
+                Arg::new("changes")
+                    .long("sound-changes")
+                    .short('c')
+                    .value_name("TXT_FILE")
+                    .help("Sets the file of sound changes, one per line, e.g. \"p > f / V_V\".")
+                    .required(true)
+            )
+            .arg(
+                Arg::new("words")
+                    .long("words")
+                    .short('w')
+                    .value_name("TXT_FILE")
+                    .help("Sets the file of words to change, separated by whitespace.")
+                    .required(true)
+            )
+            .arg(
+                Arg::new("rules")
+                    .long("language-rules")
+                    .short('r')
+                    .value_name("YAML_FILE")
+                    .help("Sets the language rules whose classes the sound changes use.")
+                    .required(false)
+            )
+    )
+    .subcommand(
+        Command::new("nativize")
+            .about("Adapts a list of foreign words to a language and prints each word before and after.")
+            .arg(
+                Arg::new("words")
+                    .long("words")
+                    .short('w')
+                    .value_name("TXT_FILE")
+                    .help("Sets the file of foreign words to adapt, separated by whitespace.")
+                    .required(true)
+            )
+            .arg(
+                Arg::new("rules")
+                    .long("language-rules")
+                    .short('r')
+                    .value_name("YAML_FILE")
+                    .help("Sets the language rules the words are adapted to.")
+                    .required(true)
+            )
+    )
+    .subcommand(
+        Command::new("identify")
+            .about("Identifies which of several languages a word or text is in, with the confidence of each language.")
+            .arg(
+                Arg::new("models")
+                    .value_name("YAML_FILE")
+                    .help("Sets the rules files of the languages to identify, e.g. elvish.yaml dwarvish.yaml.")
+                    .num_args(2..)
+                    .required(true)
+            )
+            .arg(
+                Arg::new("text")
+                    .long("text")
+                    .short('t')
+                    .value_name("TEXT")
+                    .help("Sets the word or text to identify.")
+                    .required(true)
+            )
+    )
+    .subcommand(
+        Command::new("compare")
+            .about("Compares two rules files and prints how different their languages are.")
+            .arg(
+                Arg::new("first")
+                    .value_name("YAML_FILE")
+                    .help("Sets the first rules file.")
+                    .required(true)
+            )
+            .arg(
+                Arg::new("second")
+                    .value_name("YAML_FILE")
+                    .help("Sets the second rules file.")
+                    .required(true)
+            )
+    )
+    .subcommand(
+        Command::new("family")
+            .about("Generates a proto-language and daughter languages derived from it by random sound changes.")
+            .arg(
+                Arg::new("rules")
+                    .long("language-rules")
+                    .short('r')
+                    .value_name("YAML_FILE")
+                    .help("Sets the language rules of the proto-language.")
+                    .required(true)
+            )
+            .arg(
+                Arg::new("daughters")
+                    .long("daughters")
+                    .short('d')
+                    .value_name("COUNT")
+                    .help("Sets the number of daughter languages.")
+                    .value_parser(clap::value_parser!(usize))
+                    .default_value("3")
+            )
+            .arg(
+                Arg::new("words")
+                    .long("words")
+                    .short('w')
+                    .value_name("COUNT")
+                    .help("Sets the number of words in the proto-lexicon.")
+                    .value_parser(clap::value_parser!(u32))
+                    .default_value("20")
+            )
+            .arg(
+                Arg::new("changes")
+                    .long("sound-changes")
+                    .short('c')
+                    .value_name("COUNT")
+                    .help("Sets the number of sound changes each daughter language goes through.")
+                    .value_parser(clap::value_parser!(usize))
+                    .default_value("4")
+            )
+            .arg(
+                Arg::new("output")
+                    .long("output")
+                    .short('o')
+                    .value_name("DIRECTORY")
+                    .help("Sets the directory the rules of the daughter languages are saved in, instead of printing them out.")
+            )
+            .arg(
+                Arg::new("force")
+                    .long("force")
+                    .help("Overwrites the files the rules of the daughter languages are saved in if they already exist.")
+                    .action(ArgAction::SetTrue)
+            )
+    )
+    .subcommand(
+        Command::new("invent")
+            .about("Synthesizes the rules of a brand-new language from the seed and a few knobs.")
+            .arg(
+                Arg::new("preset")
+                    .long("preset")
+                    .short('p')
+                    .value_name("PRESET")
+                    .help("Sets the typological preset the knobs start from: balanced, polynesian, germanic or bantu.")
+                    .value_parser(["balanced", "polynesian", "germanic", "bantu"])
+                    .default_value("balanced")
+            )
+            .arg(
+                Arg::new("consonants")
+                    .long("consonants")
+                    .short('c')
+                    .value_name("COUNT")
+                    .help("Sets the number of consonants, from 4 to 19.")
+                    .value_parser(clap::value_parser!(usize))
+                    .required(false)
+            )
+            .arg(
+                Arg::new("vowels")
+                    .long("vowels")
+                    .short('v')
+                    .value_name("COUNT")
+                    .help("Sets the number of vowels, from 2 to 6.")
+                    .value_parser(clap::value_parser!(usize))
+                    .required(false)
+            )
+            .arg(
+                Arg::new("complexity")
+                    .long("complexity")
+                    .short('x')
+                    .value_name("LEVEL")
+                    .help("Sets how complex the syllables are, from 1 (open syllables) to 3 (clusters in onsets and codas).")
+                    .value_parser(clap::value_parser!(usize))
+                    .required(false)
+            )
+            .arg(
+                Arg::new("output")
+                    .long("output")
+                    .short('o')
+                    .value_name("YAML_FILE")
+                    .help("Sets the file the rules are saved in, or - to print them out.")
+            )
+            .arg(
+                Arg::new("force")
+                    .long("force")
+                    .help("Overwrites the file the rules are saved in if it already exists.")
+                    .action(ArgAction::SetTrue)
+            )
+    )
+    .subcommand(
+        Command::new("blend")
+            .about("Blends several rules files with weights into one, and generates words with it.")
+            .arg(
+                Arg::new("models")
+                    .value_name("YAML_FILE[:WEIGHT]")
+                    .help("Sets the rules files to blend, each optionally followed by its weight, e.g. elvish.yaml:3.")
+                    .num_args(2..)
+                    .required(true)
+            )
+            .arg(
+                Arg::new("output")
+                    .long("output")
+                    .short('o')
+                    .value_name("YAML_FILE")
+                    .help("Sets the file the blended rules are saved in, or - to print them out.")
+            )
+            .arg(
+                Arg::new("force")
+                    .long("force")
+                    .help("Overwrites the file the blended rules are saved in if it already exists.")
+                    .action(ArgAction::SetTrue)
+            )
+    )
     .arg_required_else_help(true)
-    .get_matches();
-    
-    // Extract path from cmd line arg.
-    let mut path = cmd.get_one::<String>("sample").unwrap_or({
-        cmd.get_one::<String>("rules").unwrap_or(&String::from(""))
-    }).to_owned();
-
-    // Prepend correct paths if needed.
-    if !path.contains("assets") {
-        let p = format!(".\\assets\\examples\\{}", &path);
-        if Path::new(&p).exists() {
-            path.insert_str(0, ".\\assets\\examples\\");
-        }
-        else {
-            path.insert_str(0, ".\\assets\\local\\");
-        }
-    }
-    (path, cmd.contains_id("sample"))
 }
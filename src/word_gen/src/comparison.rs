@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::verification;
+
+// The differences between two rules: the letters and patterns only one of them has, how the probabilities of
+// the continuations of their shared patterns shifted, and how much they diverge overall.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub first_letters: String,
+    pub second_letters: String,
+    pub first_patterns: Vec<String>,
+    pub second_patterns: Vec<String>,
+    // The shared patterns whose continuations shifted, from the most to the least divergent.
+    pub shifts: Vec<Shift>,
+    // The average divergence of every pattern, from 0 for the same rules to 1 for rules with no patterns in common.
+    pub divergence: f32,
+}
+
+// How the continuations of a pattern shifted between two rules, with the Jensen-Shannon divergence of their
+// probabilities, from 0 to 1, and each continuation tupled with its probability in the first and second rules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shift {
+    pub pattern: String,
+    pub divergence: f32,
+    pub continuations: Vec<(String, f32, f32)>,
+}
+
+impl Comparison {
+    // Reports the differences between the rules, named first and second, with the 10 largest shifts.
+    pub fn report(&self, first: &str, second: &str) -> String {
+        let mut lines = vec![
+            format!("Letters only in {first}: {}", self.first_letters),
+            format!("Letters only in {second}: {}", self.second_letters),
+            format!("Patterns only in {first} ({}): {}", self.first_patterns.len(), self.first_patterns.join(", ")),
+            format!("Patterns only in {second} ({}): {}", self.second_patterns.len(), self.second_patterns.join(", ")),
+            format!("Largest shifts in shared patterns ({} shifted):", self.shifts.len()),
+        ];
+        for shift in self.shifts.iter().take(10) {
+            lines.push(format!("  \"{}\": {:.3}", shift.pattern, shift.divergence));
+            for (continuation, p, q) in shift.continuations.iter().filter(|(_, p, q)| p != q) {
+                lines.push(format!("    \"{continuation}\": {:.1}% > {:.1}%", p * 100.0, q * 100.0));
+            }
+        }
+        lines.push(format!("Divergence: {:.3}", self.divergence));
+        lines.join("\n")
+    }
+}
+
+// Compares two rules. Patterns only one of the rules has diverge by 1, and the divergence of shared patterns is
+// the Jensen-Shannon divergence of the probabilities of their continuations.
+pub fn compare_rules<'a>(first: &BTreeMap<String, BTreeMap<String, u32>>, second: &BTreeMap<String, BTreeMap<String, u32>>) -> Result<Comparison, &'a str> {
+    if verification::verify_rules(first).is_err() || verification::verify_rules(second).is_err() {
+        return Err("Rules to compare are not valid.")
+    }
+
+    // Compare the alphabets.
+    let alphabet = |rules: &BTreeMap<String, BTreeMap<String, u32>>| -> BTreeSet<char> {
+        rules["alphabet"].first_key_value().unwrap().0.chars().collect()
+    };
+    let (first_alphabet, second_alphabet) = (alphabet(first), alphabet(second));
+
+    // Compare the patterns.
+    let patterns = |rules: &BTreeMap<String, BTreeMap<String, u32>>| -> BTreeSet<String> {
+        rules.keys().filter(|k| !verification::is_section(k)).cloned().collect()
+    };
+    let (first_patterns, second_patterns) = (patterns(first), patterns(second));
+
+    let mut shifts = Vec::new();
+    for pattern in first_patterns.intersection(&second_patterns) {
+        let first_probabilities = probabilities(&first[pattern]);
+        let second_probabilities = probabilities(&second[pattern]);
+        let continuations: BTreeSet<&String> = first[pattern].keys().chain(second[pattern].keys()).collect();
+        let continuations: Vec<(String, f32, f32)> = continuations.into_iter().map(|c| (
+            c.to_owned(),
+            first_probabilities.get(c).copied().unwrap_or(0.0),
+            second_probabilities.get(c).copied().unwrap_or(0.0),
+        )).collect();
+
+        let divergence = jensen_shannon(&continuations);
+        shifts.push(Shift { pattern: pattern.to_owned(), divergence, continuations });
+    }
+
+    // Average the divergence of every pattern, where the patterns only one of the rules has diverge entirely.
+    let only_first: Vec<String> = first_patterns.difference(&second_patterns).cloned().collect();
+    let only_second: Vec<String> = second_patterns.difference(&first_patterns).cloned().collect();
+    let total = shifts.len() + only_first.len() + only_second.len();
+    let sum = shifts.iter().map(|s| s.divergence).sum::<f32>() + (only_first.len() + only_second.len()) as f32;
+    let divergence = if total == 0 { 0.0 } else { sum / total as f32 };
+
+    shifts.retain(|s| s.divergence > 0.0);
+    shifts.sort_by(|a, b| b.divergence.partial_cmp(&a.divergence).unwrap().then(a.pattern.cmp(&b.pattern)));
+
+    Ok(Comparison {
+        first_letters: first_alphabet.difference(&second_alphabet).collect(),
+        second_letters: second_alphabet.difference(&first_alphabet).collect(),
+        first_patterns: only_first,
+        second_patterns: only_second,
+        shifts,
+        divergence,
+    })
+}
+
+// Gets the probability of each continuation of a pattern from their weights.
+fn probabilities(continuations: &BTreeMap<String, u32>) -> BTreeMap<&String, f32> {
+    let sum: u32 = continuations.values().sum();
+    continuations.iter().map(|(c, w)| (c, if sum == 0 { 0.0 } else { *w as f32 / sum as f32 })).collect()
+}
+
+// Calculates the Jensen-Shannon divergence, with base 2 logarithms so it's between 0 and 1, of the probabilities
+// of the continuations in the first and second rules. A pattern with no possible continuations in only one of them diverges by 1.
+fn jensen_shannon(continuations: &[(String, f32, f32)]) -> f32 {
+    let first: f32 = continuations.iter().map(|c| c.1).sum();
+    let second: f32 = continuations.iter().map(|c| c.2).sum();
+    if first == 0.0 || second == 0.0 {
+        return if first == second { 0.0 } else { 1.0 }
+    }
+
+    let kullback_leibler = |p: f32, m: f32| if p == 0.0 { 0.0 } else { p * (p / m).log2() };
+    let divergence: f32 = continuations.iter().map(|(_, p, q)| {
+        let m = (p + q) / 2.0;
+        (kullback_leibler(*p, m) + kullback_leibler(*q, m)) / 2.0
+    }).sum();
+    divergence.clamp(0.0, 1.0)
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_rules(alphabet: &str, patterns: &[(&str, &[(&str, u32)])]) -> BTreeMap<String, BTreeMap<String, u32>> {
+        let mut rules: BTreeMap<String, BTreeMap<String, u32>> = patterns.iter()
+            .map(|(p, c)| (p.to_string(), c.iter().map(|(k, v)| (k.to_string(), *v)).collect()))
+            .collect();
+        rules.insert("alphabet".to_owned(), BTreeMap::from([(alphabet.to_owned(), 0)]));
+        rules.insert("word_length".to_owned(), BTreeMap::from([("min".to_owned(), 1), ("avg".to_owned(), 3), ("max".to_owned(), 5)]));
+        rules
+    }
+
+    #[test]
+    fn jensen_shannon_correct_values() {
+        let continuation = |p: f32, q: f32| ("a".to_owned(), p, q);
+        assert_eq!(jensen_shannon(&[continuation(0.5, 0.5), continuation(0.5, 0.5)]), 0.0);
+        assert_eq!(jensen_shannon(&[continuation(1.0, 0.0), continuation(0.0, 1.0)]), 1.0);
+        assert_eq!(jensen_shannon(&[continuation(1.0, 0.0)]), 1.0);
+        let divergence = jensen_shannon(&[continuation(1.0, 0.5), continuation(0.0, 0.5)]);
+        assert!((divergence - 0.311_278).abs() < 1e-5);
+    }
+
+    #[test]
+    fn compare_rules_correct_values() {
+        let first = get_rules("ab", &[(" ", &[("a", 1), ("b", 1)]), ("a", &[(" ", 1)]), ("b", &[(" ", 1)])]);
+        let second = get_rules("ac", &[(" ", &[("a", 1)]), ("a", &[(" ", 2)]), ("c", &[(" ", 1)])]);
+        let result = compare_rules(&first, &second).expect("Failed to compare rules.");
+
+        assert_eq!((result.first_letters.as_str(), result.second_letters.as_str()), ("b", "c"));
+        assert_eq!((result.first_patterns.as_slice(), result.second_patterns.as_slice()), (["b".to_owned()].as_slice(), ["c".to_owned()].as_slice()));
+        assert_eq!(result.shifts.len(), 1);
+        assert_eq!(result.shifts[0].pattern, " ");
+        assert_eq!(result.shifts[0].continuations, [("a".to_owned(), 0.5, 1.0), ("b".to_owned(), 0.5, 0.0)]);
+        assert!((result.divergence - (2.0 + result.shifts[0].divergence) / 4.0).abs() < 1e-6);
+
+        let report = result.report("first", "second");
+        assert!(report.starts_with("Letters only in first: b\nLetters only in second: c\nPatterns only in first (1): b\n"));
+        assert!(report.contains("  \" \": ") && report.contains("    \"b\": 50.0% > 0.0%\n"));
+
+        let same = compare_rules(&first, &first).expect("Failed to compare rules.");
+        assert!(same.shifts.is_empty() && same.divergence == 0.0);
+    }
+
+    #[test]
+    fn compare_rules_invalid_values() {
+        let rules = get_rules("a", &[(" ", &[("a", 1)]), ("a", &[(" ", 1)])]);
+        assert!(compare_rules(&rules, &BTreeMap::new()).is_err());
+    }
+}
+// TESTS END
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use rand::{RngCore, SeedableRng};
+
+use crate::generator::Language;
+use crate::rng::Pcg32;
+use crate::{blend, seed, verification};
+
+// A dialect continuum, with the rules of each language from one end to the other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Continuum {
+    pub languages: Vec<BTreeMap<String, BTreeMap<String, u32>>>,
+}
+
+impl Continuum {
+    // Generates a lexicon shared by the languages, where the word at each index is generated from the seed of that index
+    // in every language, so each index is linked across the continuum and is the word generate_word_at gives for it.
+    pub fn lexicon(&self, seed: u64, amount: u32) -> Result<Vec<Vec<String>>, &str> {
+        let mut result = Vec::new();
+        for rules in &self.languages {
+            verification::verify_rules(rules)?;
+            let language = Language::new(rules);
+            let mut words = Vec::new();
+            for i in 0..amount {
+                let mut rng = Pcg32::seed_from_u64(seed::index_seed(seed, i as u64));
+                words.push(language.generate_word(&mut rng)?);
+            }
+            result.push(words);
+        }
+        Ok(result)
+    }
+}
+
+// Generates a dialect continuum of the given number of languages along a line through the anchor rules, in order.
+// The first and last languages have the patterns of the first and last anchors, and the ones between them are blends
+// of their nearest anchors, weighted by how close they are. The continuations of every language then drift at random
+// by up to drift per step, e.g. 0.1, drifting further from the anchors towards the middle, so neighbors stay similar.
+pub fn generate_continuum<'a>(rng: &mut Pcg32, anchors: &[&BTreeMap<String, BTreeMap<String, u32>>], languages: usize, drift: f32) -> Result<Continuum, &'a str> {
+    if anchors.len() < 2 || languages < anchors.len() {
+        return Err("A continuum needs at least 2 anchors and as many languages.")
+    }
+    if drift < 0.0 {
+        return Err("Drift can't be negative.")
+    }
+
+    // Blend the two nearest anchors of each language, weighted by its position between them.
+    let steps = languages - 1;
+    let segments = anchors.len() - 1;
+    let mut result = Vec::new();
+    for n in 0..languages {
+        let position = n * segments;
+        let segment = (position / steps).min(segments - 1);
+        let local = (position - segment * steps) as u32;
+        let weights = [steps as u32 - local, local];
+        result.push(blend::blend_rules(&anchors[segment..=segment + 1], &weights)?);
+    }
+
+    // Every continuation takes a random walk from the first to the last language, bent to come back to 0 at the end,
+    // and its weight is scaled by the exponential of its walk.
+    let mut keys: Vec<(String, String)> = Vec::new();
+    for anchor in anchors {
+        for (pattern, continuations) in anchor.iter().filter(|(k, _)| !verification::is_section(k)) {
+            keys.extend(continuations.keys().map(|c| (pattern.to_owned(), c.to_owned())));
+        }
+    }
+    keys.sort();
+    keys.dedup();
+
+    for (pattern, continuation) in keys {
+        let mut walk = vec![0.0; languages];
+        for n in 1..languages {
+            let noise = rng.next_u32() as f32 / u32::MAX as f32 * 2.0 - 1.0;
+            walk[n] = walk[n - 1] + noise * drift;
+        }
+        for (n, rules) in result.iter_mut().enumerate() {
+            let bridge = walk[n] - walk[steps] * n as f32 / steps as f32;
+            if let Some(weight) = rules.get_mut(&pattern).and_then(|p| p.get_mut(&continuation)) {
+                if *weight > 0 {
+                    *weight = ((*weight as f32 * bridge.exp()).round() as u32).max(1);
+                }
+            }
+        }
+    }
+
+    Ok(Continuum { languages: result })
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_rules(alphabet: &str, continuations: &[(&str, u32)]) -> BTreeMap<String, BTreeMap<String, u32>> {
+        BTreeMap::from([
+            ("alphabet".to_owned(), BTreeMap::from([(alphabet.to_owned(), 0)])),
+            ("word_length".to_owned(), BTreeMap::from([("min".to_owned(), 1), ("avg".to_owned(), 3), ("max".to_owned(), 5)])),
+            (" ".to_owned(), continuations.iter().map(|(c, w)| (c.to_string(), *w)).collect()),
+            ("a".to_owned(), BTreeMap::from([(" ".to_owned(), 1)])),
+            ("b".to_owned(), BTreeMap::from([(" ".to_owned(), 1)])),
+        ])
+    }
+
+    #[test]
+    fn generate_continuum_interpolates_anchors() {
+        let first = get_rules("ab", &[("a", 1), ("b", 0)]);
+        let second = get_rules("ab", &[("a", 0), ("b", 1)]);
+        let mut rng = Pcg32::seed_from_u64(0);
+        let result = generate_continuum(&mut rng, &[&first, &second], 5, 0.0).expect("Failed to generate continuum.");
+
+        let weights: Vec<(u32, u32)> = result.languages.iter().map(|r| (r[" "]["a"], r[" "]["b"])).collect();
+        assert_eq!(weights, [(1000, 0), (750, 250), (500, 500), (250, 750), (0, 1000)]);
+    }
+
+    #[test]
+    fn generate_continuum_drifts_between_anchors() {
+        let first = get_rules("ab", &[("a", 1), ("b", 1)]);
+        let mut rng = Pcg32::seed_from_u64(0);
+        let result = generate_continuum(&mut rng, &[&first, &first], 5, 0.5).expect("Failed to generate continuum.");
+
+        assert_eq!(result.languages[0][" "]["a"], 500);
+        assert_eq!(result.languages[4][" "]["a"], 500);
+        assert!(result.languages[1..4].iter().any(|r| r[" "]["a"] != 500));
+    }
+
+    #[test]
+    fn generate_continuum_invalid_values() {
+        let first = get_rules("ab", &[("a", 1)]);
+        let mut rng = Pcg32::seed_from_u64(0);
+        assert!(generate_continuum(&mut rng, &[&first], 3, 0.1).is_err());
+        assert!(generate_continuum(&mut rng, &[&first, &first, &first], 2, 0.1).is_err());
+        assert!(generate_continuum(&mut rng, &[&first, &first], 3, -0.1).is_err());
+    }
+}
+// TESTS END
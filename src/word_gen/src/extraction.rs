@@ -0,0 +1,448 @@
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+// The formats a sample text can be given in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Plain,
+    Html,
+    Markdown,
+    Epub,
+}
+
+impl Format {
+    // Returns the format matching a name given as an argument, e.g. "html" or "md".
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "plain" | "text" | "txt" => Some(Self::Plain),
+            "html" | "htm" | "xhtml" => Some(Self::Html),
+            "markdown" | "md" => Some(Self::Markdown),
+            "epub" => Some(Self::Epub),
+            _ => None,
+        }
+    }
+}
+
+// Elements whose content is never shown as prose.
+const HIDDEN_ELEMENTS: [&str; 8] = ["head", "script", "style", "noscript", "template", "svg", "math", "code"];
+
+// Elements that don't break words when removed, e.g. "<b>w</b>ord" is a single word.
+const INLINE_ELEMENTS: [&str; 14] = ["a", "abbr", "b", "bdi", "bdo", "em", "i", "mark", "q", "s", "small", "span", "strong", "u"];
+
+// Detects the format of a sample text, first by its file extension and then by its contents.
+pub fn detect_format(path: &str, bytes: &[u8]) -> Format {
+    let extension = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("");
+    if let Some(format) = Format::from_name(extension) {
+        return format
+    }
+
+    // Every EPUB is a zip archive, which starts with the local file header signature.
+    if bytes.starts_with(b"PK\x03\x04") {
+        return Format::Epub
+    }
+
+    let text = String::from_utf8_lossy(bytes).to_lowercase();
+    let start = text.trim_start();
+    if start.starts_with("<!doctype html") || start.starts_with("<html") || start.starts_with("<?xml") || text.contains("<body") {
+        return Format::Html
+    }
+
+    // Markdown is recognized by its headings, fenced code blocks or links.
+    let markdown = text.lines().any(|line| {
+        let line = line.trim_start();
+        line.starts_with("# ") || line.starts_with("## ") || line.starts_with("```")
+    }) || text.contains("](");
+    if markdown { Format::Markdown } else { Format::Plain }
+}
+
+// Extracts the visible prose of a sample text in the given format.
+pub fn extract_text(bytes: &[u8], format: Format) -> Result<String, Box<dyn std::error::Error>> {
+    match format {
+        Format::Plain => Ok(String::from_utf8_lossy(bytes).into_owned()),
+        Format::Html => Ok(text_from_html(&String::from_utf8_lossy(bytes))),
+        Format::Markdown => Ok(text_from_markdown(&String::from_utf8_lossy(bytes))),
+        Format::Epub => text_from_epub(bytes),
+    }
+}
+
+// Removes all markup from HTML, leaving only the text that would be visible in a browser.
+pub fn text_from_html(html: &str) -> String {
+    let mut result = String::new();
+    let mut rest = html;
+    // The element whose content is skipped until its closing tag, if any.
+    let mut hidden: Option<String> = None;
+
+    while let Some(start) = rest.find('<') {
+        if hidden.is_none() {
+            result.push_str(&decode_entities(&rest[..start]));
+        }
+        rest = &rest[start..];
+
+        // A '<' that doesn't start a tag, e.g. in "a < b", or whose tag is never closed, is part of the text.
+        let starts_tag = rest[1..].starts_with(|c: char| c.is_alphabetic() || "/!?".contains(c));
+        if !starts_tag || (!rest.starts_with("<!") && !rest.contains('>')) {
+            if hidden.is_none() {
+                result.push('<');
+            }
+            rest = &rest[1..];
+            continue
+        }
+
+        // Comments and CDATA sections may contain '>', so they are skipped by their own terminators.
+        let (tag, end) = if rest.starts_with("<!--") {
+            ("", rest.find("-->").map(|i| i + 3))
+        } else if rest.starts_with("<![CDATA[") {
+            ("", rest.find("]]>").map(|i| i + 3))
+        } else {
+            let end = rest.find('>').map(|i| i + 1);
+            (&rest[..end.unwrap_or(rest.len())], end)
+        };
+        let end = end.unwrap_or(rest.len());
+
+        let (name, closing) = tag_name(tag);
+        match &hidden {
+            Some(h) => if closing && name == *h { hidden = None },
+            None => {
+                if !closing && !tag.ends_with("/>") && HIDDEN_ELEMENTS.contains(&name.as_str()) {
+                    hidden = Some(name);
+                } else if !INLINE_ELEMENTS.contains(&name.as_str()) {
+                    result.push(' ');
+                }
+            }
+        }
+        rest = &rest[end..];
+    }
+    if hidden.is_none() {
+        result.push_str(&decode_entities(rest));
+    }
+    result
+}
+
+// Removes all Markdown syntax, leaving only the prose.
+pub fn text_from_markdown(markdown: &str) -> String {
+    let mut result = String::new();
+    let mut fenced = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+
+        // Skip fenced code blocks and link reference definitions, e.g. "[id]: https://example.com".
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+            continue
+        }
+        if fenced || (trimmed.starts_with('[') && trimmed.contains("]:")) {
+            continue
+        }
+
+        // Heading and block quote markers are not prose either.
+        result.push_str(&strip_inline_markdown(trimmed.trim_start_matches(['#', '>'])));
+        result.push('\n');
+    }
+
+    // Markdown may contain inline HTML.
+    text_from_html(&result)
+}
+
+// Extracts the prose from every document of an EPUB in reading order.
+pub fn text_from_epub(bytes: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+
+    // Find the documents listed in the spine of the package, or fall back to all documents by name.
+    let documents = match read_entry(&mut archive, "META-INF/container.xml") {
+        Some(container) => spine_documents(&mut archive, &container),
+        None => None,
+    };
+    let documents = match documents {
+        Some(d) => d,
+        None => {
+            let mut names: Vec<String> = archive.file_names()
+                .filter(|n| n.ends_with(".xhtml") || n.ends_with(".html") || n.ends_with(".htm"))
+                .map(|n| n.to_owned())
+                .collect();
+            names.sort();
+            names
+        }
+    };
+
+    let mut result = String::new();
+    for document in documents {
+        if let Some(html) = read_entry(&mut archive, &document) {
+            result.push_str(&text_from_html(&html));
+            result.push('\n');
+        }
+    }
+    Ok(result)
+}
+
+// Gets the documents in the spine of the package pointed at by container.xml, in reading order.
+fn spine_documents(archive: &mut zip::ZipArchive<Cursor<&[u8]>>, container: &str) -> Option<Vec<String>> {
+    let package_path = tags(container).into_iter()
+        .find(|t| tag_name(t).0 == "rootfile")
+        .and_then(|t| attribute(&t, "full-path"))?;
+    let package = read_entry(archive, &package_path)?;
+
+    // Paths in the package are relative to the directory of the package.
+    let directory = match package_path.rfind('/') {
+        Some(i) => &package_path[..=i],
+        None => "",
+    };
+
+    let tags = tags(&package);
+    let mut documents = Vec::new();
+    for spine_item in tags.iter().filter(|t| tag_name(t).0 == "itemref") {
+        let id = attribute(spine_item, "idref")?;
+        let href = tags.iter()
+            .filter(|t| tag_name(t).0 == "item")
+            .find(|t| attribute(t, "id").as_deref() == Some(id.as_str()))
+            .and_then(|t| attribute(t, "href"))?;
+        documents.push(format!("{directory}{}", decode_entities(&href)));
+    }
+    Some(documents)
+}
+
+// Reads an entry of a zip archive as a string, if it exists.
+fn read_entry(archive: &mut zip::ZipArchive<Cursor<&[u8]>>, name: &str) -> Option<String> {
+    let mut file = archive.by_name(name).ok()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+// Removes the inline syntax of a single Markdown line: images, links, code spans, emphasis and block markers.
+fn strip_inline_markdown(line: &str) -> String {
+    let mut result = String::new();
+    let chars: Vec<char> = line.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            // Images are not prose, so their alt text is dropped along with the url.
+            '!' if chars.get(i + 1) == Some(&'[') => {
+                let close = find_char(&chars, i + 2, ']');
+                i = skip_url(&chars, close.map_or(chars.len(), |c| c + 1));
+                result.push(' ');
+            }
+            // Links keep their text but lose their url.
+            '[' => {
+                match find_char(&chars, i + 1, ']') {
+                    Some(close) => {
+                        result.extend(&chars[i + 1..close]);
+                        i = skip_url(&chars, close + 1);
+                    }
+                    None => i += 1,
+                }
+            }
+            // Code spans are removed entirely.
+            '`' => {
+                i = find_char(&chars, i + 1, '`').map_or(chars.len(), |c| c + 1);
+                result.push(' ');
+            }
+            // Emphasis doesn't break words.
+            '*' | '_' | '~' => i += 1,
+            c => {
+                result.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    // Bare urls are not prose.
+    let words: Vec<&str> = result.split(' ')
+        .filter(|w| !w.contains("://") && !w.starts_with("www."))
+        .collect();
+    words.join(" ")
+}
+
+// Gets the index of the first c in chars at or after start.
+fn find_char(chars: &[char], start: usize, c: char) -> Option<usize> {
+    chars.iter().skip(start).position(|x| *x == c).map(|p| p + start)
+}
+
+// Skips the "(url)" following a link or image, if there is one, and returns the next index.
+fn skip_url(chars: &[char], start: usize) -> usize {
+    if chars.get(start) == Some(&'(') {
+        find_char(chars, start, ')').map_or(chars.len(), |c| c + 1)
+    } else {
+        start
+    }
+}
+
+// Gets all tags in a markup document, e.g. "<item id=\"a\" />".
+fn tags(markup: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut rest = markup;
+    while let Some(start) = rest.find('<') {
+        let end = match rest[start..].find('>') {
+            Some(e) => start + e + 1,
+            None => break,
+        };
+        result.push(rest[start..end].to_owned());
+        rest = &rest[end..];
+    }
+    result
+}
+
+// Gets the lowercase name of a tag, without any namespace, and whether it is a closing tag.
+fn tag_name(tag: &str) -> (String, bool) {
+    let tag = tag.trim_start_matches('<');
+    let closing = tag.starts_with('/');
+    let name: String = tag.trim_start_matches('/')
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == ':' || *c == '-')
+        .collect();
+    let name = name.rsplit(':').next().unwrap_or("").to_lowercase();
+    (name, closing)
+}
+
+// Gets the value of an attribute of a tag.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let mut rest = tag;
+    while let Some(i) = rest.find(name) {
+        let preceding = rest[..i].chars().last();
+        let after = rest[i + name.len()..].trim_start();
+        rest = &rest[i + name.len()..];
+
+        // Make sure the match is the whole attribute name and not e.g. "id" in "idref".
+        if !preceding.is_some_and(|c| c.is_whitespace()) || !after.starts_with('=') {
+            continue
+        }
+        let value = after[1..].trim_start();
+        let quote = value.chars().next()?;
+        if quote == '"' || quote == '\'' {
+            let end = value[1..].find(quote)?;
+            return Some(value[1..=end].to_owned())
+        }
+        return Some(value.chars().take_while(|c| !c.is_whitespace() && *c != '>' && *c != '/').collect())
+    }
+    None
+}
+
+// Replaces character references such as "&amp;" and "&#233;" with the characters they represent.
+fn decode_entities(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // References are short, so a far away ';' isn't the end of one.
+        let end = match rest.find(';') {
+            Some(e) if e <= 10 => e,
+            _ => {
+                result.push('&');
+                rest = &rest[1..];
+                continue
+            }
+        };
+
+        let name = &rest[1..end];
+        let decoded = if let Some(hex) = name.strip_prefix("#x").or(name.strip_prefix("#X")) {
+            u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+        } else if let Some(decimal) = name.strip_prefix('#') {
+            decimal.parse::<u32>().ok().and_then(char::from_u32)
+        } else {
+            named_entity(name)
+        };
+
+        // Unknown references are replaced with a space so their name doesn't end up in a word, and soft hyphens
+        // are dropped since they only mark where a word may break.
+        match decoded {
+            Some('\u{ad}') => (),
+            decoded => result.push(decoded.unwrap_or(' ')),
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+// Gets the character of the most common named character references.
+fn named_entity(name: &str) -> Option<char> {
+    let c = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "shy" => '\u{ad}',
+        _ => {
+            // Letters with diacritics, e.g. "eacute" or "ouml".
+            let mut chars = name.chars();
+            let base = chars.next()?;
+            let mark = match chars.as_str() {
+                "acute" => '\u{301}',
+                "grave" => '\u{300}',
+                "circ" => '\u{302}',
+                "tilde" => '\u{303}',
+                "uml" => '\u{308}',
+                "ring" => '\u{30a}',
+                "cedil" => '\u{327}',
+                _ => return None,
+            };
+            return compose(base, mark)
+        }
+    };
+    Some(c)
+}
+
+// Composes a latin letter and a combining mark into a single precomposed character.
+fn compose(base: char, mark: char) -> Option<char> {
+    const TABLE: [(char, char, char); 30] = [
+        ('a', '\u{301}', 'á'), ('e', '\u{301}', 'é'), ('i', '\u{301}', 'í'), ('o', '\u{301}', 'ó'), ('u', '\u{301}', 'ú'),
+        ('a', '\u{300}', 'à'), ('e', '\u{300}', 'è'), ('i', '\u{300}', 'ì'), ('o', '\u{300}', 'ò'), ('u', '\u{300}', 'ù'),
+        ('a', '\u{302}', 'â'), ('e', '\u{302}', 'ê'), ('i', '\u{302}', 'î'), ('o', '\u{302}', 'ô'), ('u', '\u{302}', 'û'),
+        ('a', '\u{303}', 'ã'), ('n', '\u{303}', 'ñ'), ('o', '\u{303}', 'õ'),
+        ('a', '\u{308}', 'ä'), ('e', '\u{308}', 'ë'), ('i', '\u{308}', 'ï'), ('o', '\u{308}', 'ö'), ('u', '\u{308}', 'ü'), ('y', '\u{308}', 'ÿ'),
+        ('a', '\u{30a}', 'å'), ('c', '\u{327}', 'ç'),
+        ('A', '\u{308}', 'Ä'), ('O', '\u{308}', 'Ö'), ('U', '\u{308}', 'Ü'), ('A', '\u{30a}', 'Å'),
+    ];
+    TABLE.iter().find(|(b, m, _)| *b == base && *m == mark).map(|(_, _, c)| *c)
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_from_html_correct_values() {
+        assert_eq!(text_from_html("<p class=\"div\">lorem</p><p>ipsum</p>").split_whitespace().collect::<Vec<_>>(), ["lorem", "ipsum"]);
+        assert_eq!(text_from_html("<a href=\"https://example.com\">lo<b>rem</b></a>"), "lorem");
+        assert_eq!(text_from_html("<head><title>title</title></head><script>var x;</script>text").trim(), "text");
+        assert_eq!(text_from_html("a<!-- <p>comment</p> -->b"), "a b");
+        assert_eq!(text_from_html("caf&eacute; &amp; cr&#232;me"), "café & crème");
+        assert_eq!(text_from_html("hy&shy;phen&#173;ated"), "hyphenated");
+        assert_eq!(text_from_html("a < b<br>c").split_whitespace().collect::<Vec<_>>(), ["a", "<", "b", "c"]);
+        assert_eq!(text_from_html("<p>x</p>y <z and more"), " x y <z and more");
+    }
+
+    #[test]
+    fn text_from_markdown_correct_values() {
+        assert_eq!(text_from_markdown("# Title\n\n**bold** and *it*alic").split_whitespace().collect::<Vec<_>>(), ["Title", "bold", "and", "italic"]);
+        assert_eq!(text_from_markdown("a [link](https://example.com) ![image](image.png) `code`").split_whitespace().collect::<Vec<_>>(), ["a", "link"]);
+        assert_eq!(text_from_markdown("text\n```\nfn main() {}\n```\n[id]: https://example.com\nmore").split_whitespace().collect::<Vec<_>>(), ["text", "more"]);
+        assert_eq!(text_from_markdown("see https://example.com <em>now</em>").split_whitespace().collect::<Vec<_>>(), ["see", "now"]);
+    }
+
+    #[test]
+    fn detect_format_correct_values() {
+        assert_eq!(detect_format("book.epub", b""), Format::Epub);
+        assert_eq!(detect_format("page.HTM", b""), Format::Html);
+        assert_eq!(detect_format("notes.md", b""), Format::Markdown);
+        assert_eq!(detect_format("sample", b"PK\x03\x04"), Format::Epub);
+        assert_eq!(detect_format("sample", b"<!DOCTYPE html><html></html>"), Format::Html);
+        assert_eq!(detect_format("sample", b"# Title\ntext"), Format::Markdown);
+        assert_eq!(detect_format("sample.txt", b"# Title\ntext"), Format::Plain);
+        assert_eq!(detect_format("sample", b"plain text"), Format::Plain);
+    }
+
+    #[test]
+    fn attribute_correct_values() {
+        assert_eq!(attribute("<itemref idref=\"c1\"/>", "id"), None);
+        assert_eq!(attribute("<itemref idref=\"c1\"/>", "idref"), Some("c1".to_owned()));
+        assert_eq!(attribute("<item id='c1' href=text.xhtml>", "href"), Some("text.xhtml".to_owned()));
+    }
+}
+// TESTS END
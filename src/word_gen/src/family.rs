@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use crate::classes::Classes;
+use crate::rng::Pcg32;
+use crate::sound_change::{self, SoundChange};
+use crate::{generator, reader};
+
+// Common sound changes the daughter languages pick from. A change is only picked if it changes some of the words
+// of the proto-language.
+const SOUND_CHANGES: [&str; 36] = [
+    // Lenition and voicing between vowels.
+    "p > f / V_V", "t > θ / V_V", "k > x / V_V", "b > v / V_V", "d > ð / V_V", "g > ɣ / V_V",
+    "p > b / V_V", "t > d / V_V", "k > g / V_V", "s > z / V_V",
+    // Palatalization before front vowels.
+    "k > tʃ / _[ei]", "g > dʒ / _[ei]", "t > ts / _i", "s > ʃ / _i", "n > ɲ / _i",
+    // Final devoicing and loss.
+    "b > p / _#", "d > t / _#", "g > k / _#", "z > s / _#", "V > / CVC_#", "n > / V_#",
+    // Assimilation and loss of consonants.
+    "n > m / _[pb]", "n > ŋ / _[kg]", "h > ", "s > h / #_", "r > l / _#", "w > v", "j > ʒ / #_",
+    // Vowel shifts.
+    "a > e / _i", "o > u", "e > i / _#", "u > o / _#", "a > ɔ / _[mn]", "e > a / _r", "i > e / _C#", "o > a / #_",
+];
+
+// The lexicon of the proto-language, the daughter languages derived from it, and the cognates of every word.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Family {
+    pub proto: Vec<String>,
+    pub daughters: Vec<Daughter>,
+}
+
+// A daughter language, with the sound changes it went through, its words and the rules learned from them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Daughter {
+    pub name: String,
+    pub changes: Vec<String>,
+    pub words: Vec<String>,
+    pub rules: BTreeMap<String, BTreeMap<String, u32>>,
+}
+
+impl Family {
+    // Gets the table of cognates, where each row is a word of the proto-language followed by the words
+    // the daughter languages derived from it.
+    pub fn cognates(&self) -> Vec<Vec<&str>> {
+        self.proto.iter().enumerate().map(|(i, word)| {
+            let mut row = vec![word.as_str()];
+            row.extend(self.daughters.iter().map(|d| d.words[i].as_str()));
+            row
+        }).collect()
+    }
+}
+
+// Generates a proto-lexicon of amount number of distinct words using rules, and derives the given number of daughter
+// languages from it, each applying the given number of sound changes picked at random.
+// Every daughter language gets rules learned from its words, so it can generate new words that sound related.
+pub fn generate_family<'a>(rng: &mut Pcg32, amount: u32, daughters: usize, changes: usize, rules: &'a BTreeMap<String, BTreeMap<String, u32>>) -> Result<Family, &'a str> {
+    if daughters == 0 || changes == 0 {
+        return Err("Daughters and changes can't be 0.")
+    }
+
+    // Generate the proto-lexicon, keeping only the first of the same words.
+    let words = generator::generate_words(rng, amount, rules)?;
+    let mut proto: Vec<String> = Vec::new();
+    for word in words.split(' ') {
+        if !proto.iter().any(|w| w == word) {
+            proto.push(word.to_owned());
+        }
+    }
+
+    // Only pick sound changes that change some of the words of the proto-language.
+    let classes = Classes::from_rules(rules)?;
+    let proto_words: Vec<&str> = proto.iter().map(|w| w.as_str()).collect();
+    let mut candidates: Vec<(&str, SoundChange)> = Vec::new();
+    for change in SOUND_CHANGES {
+        let parsed = SoundChange::parse(change, &classes)?;
+        if proto_words.iter().any(|w| parsed.apply(w) != *w) {
+            candidates.push((change, parsed));
+        }
+    }
+    if candidates.len() < changes {
+        return Err("Not enough sound changes apply to the proto-language.")
+    }
+
+    let mut result = Vec::new();
+    for i in 1..=daughters {
+        // Pick different sound changes in the order they happen.
+        let mut available = candidates.clone();
+        let (mut picked, mut parsed) = (Vec::new(), Vec::new());
+        for _ in 0..changes {
+            let (change, sound_change) = available.remove(rng.below(available.len() as u32) as usize);
+            picked.push(change.to_owned());
+            parsed.push(sound_change);
+        }
+
+        let words: Vec<String> = sound_change::apply_changes(&proto_words, &parsed).into_iter().map(|(_, w)| w).collect();
+        let text: Vec<&str> = words.iter().map(|w| w.as_str()).filter(|w| !w.is_empty()).collect();
+        if text.is_empty() {
+            return Err("Sound changes removed every word.")
+        }
+
+        result.push(Daughter {
+            name: format!("daughter-{i}"),
+            changes: picked,
+            rules: reader::rules_from_string(&text.join(" "), 3),
+            words,
+        });
+    }
+
+    Ok(Family { proto, daughters: result })
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::SeedableRng;
+
+    #[test]
+    fn sound_changes_are_valid() {
+        let classes = Classes::from_alphabet("abdefghijklmnoprstuvwz");
+        for change in SOUND_CHANGES {
+            assert!(SoundChange::parse(change, &classes).is_ok(), "{change}");
+        }
+    }
+
+    #[test]
+    fn generate_family_invalid_values() {
+        let rules = BTreeMap::new();
+        let mut rng = Pcg32::seed_from_u64(0);
+        assert!(generate_family(&mut rng, 10, 0, 3, &rules).is_err());
+        assert!(generate_family(&mut rng, 10, 3, 0, &rules).is_err());
+    }
+}
+// TESTS END
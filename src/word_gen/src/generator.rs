@@ -1,11 +1,17 @@
 use std::collections::{btree_map, BTreeMap};
 
-use rand::{rngs::StdRng, RngCore};
+use rand::SeedableRng;
 
-use crate::verification;
+use crate::classes::Classes;
+use crate::harmony::Harmony;
+use crate::orthography::Orthography;
+use crate::prosody::Prosody;
+use crate::rng::{Pcg32, ALGORITHM_VERSION};
+use crate::trace::{Candidate, Step, Trace};
+use crate::{seed, syllable, verification};
 
 // Generates amount number of words using rules.
-pub fn generate_words<'a>(rng: &mut StdRng, amount: u32, rules: &'a BTreeMap<String, BTreeMap<String, u32>>) -> Result<String, &'a str> {
+pub fn generate_words<'a>(rng: &mut Pcg32, amount: u32, rules: &'a BTreeMap<String, BTreeMap<String, u32>>) -> Result<String, &'a str> {
     // Verify the rules are valid.
     verification::verify_rules(rules)?;
 
@@ -23,17 +29,187 @@ pub fn generate_words<'a>(rng: &mut StdRng, amount: u32, rules: &'a BTreeMap<Str
     Ok(result.trim().to_owned())
 }
 
+// Generates amount number of words using rules, aiming for words with the given number of syllables instead of the average word length.
+pub fn generate_words_with_syllables<'a>(rng: &mut Pcg32, amount: u32, syllables: usize, rules: &'a BTreeMap<String, BTreeMap<String, u32>>) -> Result<String, &'a str> {
+    // Verify the rules are valid.
+    verification::verify_rules(rules)?;
+    if syllables == 0 {
+        return Err("Syllables can't be 0.")
+    }
+
+    // Setup the language to aim for the number of syllables.
+    let mut language = Language::new(rules);
+    language.syllables = Some(syllables);
+    let mut result = String::from("");
+
+    // Generate each word individually.
+    for _ in 0..amount {
+        let word = language.generate_word(rng)?;
+        result.push_str(&word);
+        result.push(' ');
+    }
+
+    Ok(result.trim().to_owned())
+}
+
+// Generates the word at an index of the words for a seed using rules, without generating the words before it.
+// The word is the same on every machine, and the same as the one at its index in generate_words_at.
+pub fn generate_word_at(seed: u64, index: u64, rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Result<String, &str> {
+    generate_words_at(seed, index, 1, rules)
+}
+
+// Generates amount number of words for a seed using rules, starting from the word at the index start.
+// Each word is generated with its own rng seeded from the seed and its index, so the words of a range are
+// the words at each of its indexes.
+pub fn generate_words_at(seed: u64, start: u64, amount: u32, rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Result<String, &str> {
+    verification::verify_rules(rules)?;
+    Language::new(rules).generate_words_at(seed, start, amount)
+}
+
+// Generates amount number of words for a seed using rules like generate_words_at, aiming for words with
+// the given number of syllables instead of the average word length.
+pub fn generate_words_at_with_syllables(seed: u64, start: u64, amount: u32, syllables: usize, rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Result<String, &str> {
+    verification::verify_rules(rules)?;
+    if syllables == 0 {
+        return Err("Syllables can't be 0.")
+    }
+
+    let mut language = Language::new(rules);
+    language.syllables = Some(syllables);
+    language.generate_words_at(seed, start, amount)
+}
+
+// A generated word with what's known about it: how likely the language is to generate it (None if it can't),
+// its length in letters, the seed and index it was generated with if it was generated by index, the pattern it ended on,
+// and the version of the rng it was generated with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordRecord {
+    pub word: String,
+    pub score: Option<f32>,
+    pub length: usize,
+    pub seed: Option<u64>,
+    pub index: Option<u64>,
+    pub ending: Option<String>,
+    pub version: u32,
+}
+
+// Generates amount number of words using rules like generate_words (or generate_words_with_syllables if the number
+// of syllables is given), returning the record of each word. The words depend on the ones before them, so the records
+// have no seed or index.
+pub fn generate_records<'a>(rng: &mut Pcg32, amount: u32, syllables: Option<usize>, rules: &'a BTreeMap<String, BTreeMap<String, u32>>) -> Result<Vec<WordRecord>, &'a str> {
+    let language = language_with_syllables(syllables, rules)?;
+    let mut records = Vec::new();
+    for _ in 0..amount {
+        let word = language.generate_word(rng)?;
+        records.push(language.record(&word, None));
+    }
+    Ok(records)
+}
+
+// Generates amount number of words for a seed using rules like generate_words_at (or generate_words_at_with_syllables
+// if the number of syllables is given), returning the record of each word, with the seed and index it was generated with.
+pub fn generate_records_at(seed: u64, start: u64, amount: u32, syllables: Option<usize>, rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Result<Vec<WordRecord>, &str> {
+    let language = language_with_syllables(syllables, rules)?;
+    let mut records = Vec::new();
+    for index in start..start.saturating_add(amount as u64) {
+        let word = language.generate_word(&mut Pcg32::seed_from_u64(seed::index_seed(seed, index)))?;
+        records.push(language.record(&word, Some((seed, index))));
+    }
+    Ok(records)
+}
+
+// Generates amount number of words using rules like generate_words (or generate_words_with_syllables if the number
+// of syllables is given), returning the trace of each word, which explains how it was generated.
+pub fn explain_words<'a>(rng: &mut Pcg32, amount: u32, syllables: Option<usize>, rules: &'a BTreeMap<String, BTreeMap<String, u32>>) -> Result<Vec<Trace>, &'a str> {
+    let language = language_with_syllables(syllables, rules)?;
+    (0..amount).map(|_| language.explain_word(rng)).collect()
+}
+
+// Generates amount number of words for a seed using rules like generate_words_at (or generate_words_at_with_syllables
+// if the number of syllables is given), returning the trace of each word, which explains how it was generated.
+pub fn explain_words_at(seed: u64, start: u64, amount: u32, syllables: Option<usize>, rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Result<Vec<Trace>, &str> {
+    let language = language_with_syllables(syllables, rules)?;
+    (start..start.saturating_add(amount as u64))
+        .map(|index| language.explain_word(&mut Pcg32::seed_from_u64(seed::index_seed(seed, index))))
+        .collect()
+}
+
+// Sets up the language of the rules, aiming for the number of syllables if it's given.
+fn language_with_syllables(syllables: Option<usize>, rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Result<Language, &str> {
+    verification::verify_rules(rules)?;
+    if syllables == Some(0) {
+        return Err("Syllables can't be 0.")
+    }
+
+    let mut language = Language::new(rules);
+    language.syllables = syllables;
+    Ok(language)
+}
+
+// Generates amount number of words using rules, each tupled with its spelling given by an orthography of the rules.
+// orthography: the name of the orthography to use, or None for the unnamed one.
+pub fn generate_spelled_words<'a>(rng: &mut Pcg32, amount: u32, rules: &'a BTreeMap<String, BTreeMap<String, u32>>, orthography: Option<&str>) -> Result<Vec<(String, String)>, &'a str> {
+    // Verify the rules are valid.
+    verification::verify_rules(rules)?;
+
+    // Setup the language and its spellings.
+    let language = Language::new(rules);
+    let orthography = Orthography::from_rules(rules, orthography)?;
+
+    // Generate each word individually and spell it. Spelling doesn't use the rng, so every orthography spells the same words.
+    let mut result = Vec::new();
+    for _ in 0..amount {
+        let word = language.generate_word(rng)?;
+        result.push((word.clone(), orthography.spell(&word)));
+    }
+
+    Ok(result)
+}
+
+// Generates amount number of words using rules, each tupled with its rendering with the stress and tones of the rules.
+// separator: what the syllables of the rendering are joined with, e.g. "-" or "".
+pub fn generate_rendered_words<'a>(rng: &mut Pcg32, amount: u32, rules: &'a BTreeMap<String, BTreeMap<String, u32>>, separator: &str) -> Result<Vec<(String, String)>, &'a str> {
+    // Verify the rules are valid.
+    verification::verify_rules(rules)?;
+
+    // Setup the language and its prosody.
+    let language = Language::new(rules);
+    let prosody = Prosody::from_rules(rules)?;
+
+    let mut result = Vec::new();
+    for _ in 0..amount {
+        let word = language.generate_word(rng)?;
+        result.push((word.clone(), prosody.render(&word, separator)));
+    }
+
+    Ok(result)
+}
+
+// Scores how likely the language of the rules is to generate each word, returning each word tupled with its score,
+// the average natural logarithm of the probability of each of its continuations, or None if the language can't generate it.
+pub fn score_words<'a>(words: &[&str], rules: &'a BTreeMap<String, BTreeMap<String, u32>>) -> Result<Vec<(String, Option<f32>)>, &'a str> {
+    // Verify the rules are valid.
+    verification::verify_rules(rules)?;
+
+    let language = Language::new(rules);
+    Ok(words.iter().map(|w| (w.to_string(), language.score(w))).collect())
+}
+
 // The language object stores the rules specified in the language rules file.
-struct Language {
+pub(crate) struct Language {
     alphabet: String,
     min: usize,
     avg: usize,
     max: usize,
     patterns: BTreeMap<String, (u32, f32, BTreeMap<u32, String>)>,
+    classes: Classes,
+    harmony: Harmony,
+    // The number of syllables to aim for, instead of the average word length, if any.
+    syllables: Option<usize>,
 }
 
 impl Language {
-    fn new(rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Self {
+    pub(crate) fn new(rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Self {
         match Self::build_language(rules) {
             Some(s) => s,
             None => panic!("Failed to build language!"),
@@ -42,10 +218,13 @@ impl Language {
 
     // Creates the language object from the language rules file.
     fn build_language(rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Option<Self> {
-        // Create a new copy of rules without the alphabet and word length limits.
+        // Create a new copy of rules with only the patterns, without the alphabet, word length limits and other sections.
+        let classes = Classes::from_rules(rules).ok()?;
+        let harmony = Harmony::from_rules(rules).ok()?;
         let mut rules = rules.clone();
         let alphabet = rules.remove("alphabet")?.first_key_value()?.0.to_owned();
         let limits: BTreeMap<String, u32> = rules.remove("word_length")?;
+        rules.retain(|k, _| !verification::is_section(k));
 
         // Get the word length limits.
         let min = *limits.get("min")? as usize;
@@ -103,10 +282,48 @@ impl Language {
             avg,
             max,
             patterns,
+            classes,
+            harmony,
+            syllables: None,
         })
     }
 
-    fn generate_word<'a>(&self, rng: &mut StdRng) -> Result<String, &'a str> {
+    // Generates the words at the indexes from start for a seed, each with the rng of its index.
+    pub(crate) fn generate_words_at<'a>(&self, seed: u64, start: u64, amount: u32) -> Result<String, &'a str> {
+        let mut words = Vec::new();
+        for index in start..start.saturating_add(amount as u64) {
+            let mut rng = Pcg32::seed_from_u64(seed::index_seed(seed, index));
+            words.push(self.generate_word(&mut rng)?);
+        }
+        Ok(words.join(" "))
+    }
+
+    // Creates the record of a word, with the seed and index it was generated with if it was generated by index.
+    fn record(&self, word: &str, at: Option<(u64, u64)>) -> WordRecord {
+        WordRecord {
+            word: word.to_owned(),
+            score: self.score(word),
+            length: word.chars().count(),
+            seed: at.map(|(seed, _)| seed),
+            index: at.map(|(_, index)| index),
+            ending: self.ending(&format!(" {word}")).map(|e| e.to_owned()),
+            version: ALGORITHM_VERSION,
+        }
+    }
+
+    pub(crate) fn generate_word<'a>(&self, rng: &mut Pcg32) -> Result<String, &'a str> {
+        self.generate(rng, None)
+    }
+
+    // Generates a word like generate_word, recording every step and candidate of generating it.
+    pub(crate) fn explain_word<'a>(&self, rng: &mut Pcg32) -> Result<Trace, &'a str> {
+        let mut trace = Trace::default();
+        self.generate(rng, Some(&mut trace))?;
+        Ok(trace)
+    }
+
+    // Generates a word, recording how in the trace if one is given, which doesn't change the word.
+    fn generate<'a>(&self, rng: &mut Pcg32, mut trace: Option<&mut Trace>) -> Result<String, &'a str> {
         let mut candidates: Vec<(f32, String)> = vec![];
         let mut current: String = String::from(" ");
         let mut l = 0;
@@ -128,43 +345,72 @@ impl Language {
 
                         // Get the start position of continuations, excluding word termination.
                         let start = terminate + 1;
+                        let mut step = Step { current: current.trim_start().to_owned(), pattern, ..Step::default() };
 
                         // If the only continuation is termination, add that and stop generating word candidates.
                         if start > map.0 {
                             candidates.push((1.0, current.clone()));
+                            if let Some(trace) = trace.as_deref_mut() {
+                                trace.candidates.push(Candidate { word: current.trim().to_owned(), closeness: 0.0, ending: 1.0, value: 1.0 });
+                                trace.steps.push(step);
+                            }
                             l = self.max;
                             break
                         }
 
                         // Get a random continuation. If there's only one option, choose that, else, pick one at random.
-                        let r = if map.0 == start { start } else { rng.next_u32() % (map.0 - start) + start };
+                        let r = if map.0 == start { start } else { rng.below(map.0 - start) + start };
 
                         // Replace potential wildcards in the continuation.
                         let raw_continuation = map.2.range(&r..).next().unwrap().1;
-                        let continuation = if raw_continuation.contains('_') {
-                            self.replace_wildcards(rng, raw_continuation, &map.2).to_owned()
+                        let wildcard = if raw_continuation.contains('_') {
+                            Some(self.replace_wildcards(rng, raw_continuation, &map.2)?)
                         }
                         else {
-                            raw_continuation.to_owned()
+                            None
                         };
+                        let continuation = wildcard.clone().unwrap_or_else(|| raw_continuation.to_owned());
+
+                        // Keep the word consistent with its first harmonic letter, if the patterns know the replacements.
+                        let continuation = self.harmony.harmonize(&current, &continuation, |c| self.patterns.contains_key(&c.to_string()));
 
                         current.push_str(&continuation);
 
                         // If the length of the current word is acceptable, add it as a candidate with relative value.
                         let len = current.len();
                         if len >= self.min && len <= self.max {
-                            // Calculate the value for the candidate based on its distance from avg and the likelihood the word should end with current pattern.
-                            let value = if len < self.avg {
-                                inverse_lerp(self.min, self.avg, len)
-                            }
-                            else {
-                                1.0 - inverse_lerp(self.avg, self.max, len)
-                            } + map.1;
+                            // Calculate the value for the candidate based on its distance from avg (or the number of syllables)
+                            // and the likelihood the word should end with current pattern.
+                            let closeness = match self.syllables {
+                                Some(target) => {
+                                    let count = syllable::count_syllables(current.trim(), &self.classes);
+                                    1.0 - (count.abs_diff(target) as f32 / target as f32).min(1.0)
+                                }
+                                None => if len < self.avg {
+                                    inverse_lerp(self.min, self.avg, len)
+                                }
+                                else {
+                                    1.0 - inverse_lerp(self.avg, self.max, len)
+                                },
+                            };
+                            let value = closeness + map.1;
                             candidates.push((value, current.clone()));
+                            if let Some(trace) = trace.as_deref_mut() {
+                                step.candidate = Some(trace.candidates.len());
+                                trace.candidates.push(Candidate { word: current.trim().to_owned(), closeness, ending: map.1, value });
+                            }
                         }
 
                         l += continuation.len();
 
+                        if let Some(trace) = trace.as_deref_mut() {
+                            step.draw = if map.0 == start { None } else { Some((r, start, map.0 - 1)) };
+                            step.picked = raw_continuation.to_owned();
+                            step.wildcard = wildcard;
+                            step.continuation = continuation;
+                            trace.steps.push(step);
+                        }
+
                         break
                     },
                     None => continue,
@@ -172,16 +418,147 @@ impl Language {
             }
         }
 
-        // Get the candidate with the highest value.
-        candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
-        match candidates.first() {
-            Some(word) => Ok(word.1.trim().to_owned()),
-            None => Err("No word found."),
+        // Get the candidate with the highest value, the first one of those tied with it.
+        let best = candidates.iter().map(|c| c.0).fold(f32::MIN, f32::max);
+        let chosen = candidates.iter().position(|c| c.0 == best).ok_or("No word found.")?;
+        let word = candidates[chosen].1.trim().to_owned();
+        if let Some(trace) = trace {
+            trace.chosen = chosen;
+            trace.word = word.clone();
+        }
+        Ok(word)
+    }
+
+    // Calculates how likely the language is to generate a word, as the average natural logarithm of the probability of
+    // each continuation and of ending the word. Returns None if the language can't generate the word, e.g. if it breaks harmony.
+    pub(crate) fn score(&self, word: &str) -> Option<f32> {
+        self.likelihood(word).map(|(sum, steps)| sum / steps as f32)
+    }
+
+    // Calculates the natural logarithm of the probability of the language generating a word, as the sum for each
+    // continuation and ending the word. Returns None if the language can't generate the word.
+    pub(crate) fn log_likelihood(&self, word: &str) -> Option<f32> {
+        self.likelihood(word).map(|(sum, _)| sum)
+    }
+
+    // Sums the natural logarithms of the probabilities of each continuation of a word and of ending it, tupled with their number.
+    fn likelihood(&self, word: &str) -> Option<(f32, usize)> {
+        if !self.harmony.is_harmonic(word) {
+            return None
+        }
+
+        let (length, mut sum, mut steps) = self.follow(word);
+        if length < word.len() {
+            return None
+        }
+
+        // Words ending on a pattern that never terminates can still be generated, but are very unlikely.
+        let current = format!(" {word}");
+        let termination = self.find_pattern(&current).map_or(0.0, |map| map.1);
+        sum += termination.max(0.001).ln();
+        steps += 1;
+
+        Some((sum, steps))
+    }
+
+    // Calculates how much of the start of a word the language can generate, as its length in bytes, tupled with
+    // the average natural logarithm of the probability of each continuation in it (0 if there are none).
+    pub(crate) fn score_start(&self, word: &str) -> (usize, f32) {
+        let (length, sum, steps) = self.follow(word);
+        (length, if steps == 0 { 0.0 } else { sum / steps as f32 })
+    }
+
+    // Follows the most likely continuations matching the word for as long as possible, returning the length in bytes
+    // of the start of the word they matched, tupled with the sum of the natural logarithms of their probabilities and their number.
+    fn follow(&self, word: &str) -> (usize, f32, usize) {
+        let mut current = String::from(" ");
+        let mut rest = word;
+        let mut sum = 0.0;
+        let mut steps = 0;
+
+        while !rest.is_empty() {
+            let Some(map) = self.find_pattern(&current) else { break };
+
+            // Find the most likely continuation that matches the start of the rest of the word.
+            let best = self.continuations(map)
+                .filter_map(|(p, c)| self.match_continuation(&c, rest, &map.2).map(|(l, w)| (p / w as f32, l)))
+                .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+            let Some((probability, length)) = best else { break };
+
+            sum += probability.ln();
+            steps += 1;
+            current.push_str(&rest[..length]);
+            rest = &rest[length..];
+        }
+
+        (word.len() - rest.len(), sum, steps)
+    }
+
+    // Finds the continuations of the longest pattern that matches the end of current.
+    fn find_pattern(&self, current: &str) -> Option<&(u32, f32, BTreeMap<u32, String>)> {
+        self.ending(current).and_then(|pattern| self.patterns.get(pattern))
+    }
+
+    // Gets the longest pattern, of at most 3 characters, that matches the end of current, e.g. the pattern a word ended on.
+    fn ending<'b>(&self, current: &'b str) -> Option<&'b str> {
+        for i in (0..3).rev() {
+            let split_pos = current.char_indices().nth_back(i).unwrap_or((current.len(), ' ')).0;
+            if split_pos == current.len() { continue }
+            if self.patterns.contains_key(&current[split_pos..]) {
+                return Some(&current[split_pos..])
+            }
+        }
+        None
+    }
+
+    // Gets every continuation the rng can pick for a pattern, tupled with the probability of it being picked.
+    fn continuations<'a>(&self, map: &'a (u32, f32, BTreeMap<u32, String>)) -> impl Iterator<Item = (f32, String)> + 'a {
+        // The rng picks r from start to the last valid continuation (exclusive), just like in generate_word.
+        let mut terminate: u32 = 0;
+        if let Some((k, v)) = map.2.first_key_value() {
+            if *v == " " { terminate = *k }
         }
+        let start = terminate + 1;
+        let end = if map.0 == start { start + 1 } else { map.0 };
+
+        let mut previous = 0;
+        map.2.iter().filter_map(move |(k, v)| {
+            let low = previous.max(start);
+            let high = (*k + 1).min(end);
+            previous = *k + 1;
+            if high <= low || start > map.0 { return None }
+            Some(((high - low) as f32 / (end - start) as f32, v.to_owned()))
+        })
     }
 
-    // Replace all wildcard characters (_) in string.
-    fn replace_wildcards(&self, rng: &mut StdRng, string: &str, map: &BTreeMap<u32, String>) -> String {
+    // Gets the length of the start of rest that matches a continuation, if any, tupled with the number of letters
+    // the wildcard in the continuation could have been replaced with (1 if there is no wildcard).
+    // Wildcards match any letter of the alphabet, as long as the result is not another continuation of the pattern.
+    fn match_continuation(&self, continuation: &str, rest: &str, map: &BTreeMap<u32, String>) -> Option<(usize, usize)> {
+        let length: usize = rest.chars().take(continuation.chars().count()).map(|c| c.len_utf8()).sum();
+        let candidate = &rest[..length];
+        if candidate.chars().count() != continuation.chars().count() {
+            return None
+        }
+        if !continuation.contains('_') {
+            return if candidate == continuation { Some((length, 1)) } else { None }
+        }
+
+        // Every wildcard of a continuation is replaced with the same letter.
+        let replacements: Vec<String> = self.alphabet.chars()
+            .map(|c| continuation.replace('_', &c.to_string()))
+            .filter(|r| !map.values().any(|v| v == r))
+            .collect();
+        if replacements.iter().any(|r| r == candidate) { Some((length, replacements.len())) } else { None }
+    }
+
+    // Replace all wildcard characters (_) in string. Fails if every letter would make another continuation of the pattern.
+    fn replace_wildcards<'a>(&self, rng: &mut Pcg32, string: &str, map: &BTreeMap<u32, String>) -> Result<String, &'a str> {
+        let possible = self.alphabet.chars().any(|c| !map.values().any(|v| *v == string.replace('_', &c.to_string())));
+        if !possible {
+            return Err("No letter is left to replace a wildcard with.")
+        }
+
         let mut candidate = String::from("");
         let mut found = false;
         while !found {
@@ -191,12 +568,12 @@ impl Language {
                 if candidate == *v { found = false; }
             }
         }
-        candidate
+        Ok(candidate)
     }
 
     // Get a random character that is not represented by the existing rules of a pattern.
-    fn get_wildcard(&self, rng: &mut StdRng) -> char {
-        let i = rng.next_u32() as usize % self.alphabet.len();
+    fn get_wildcard(&self, rng: &mut Pcg32) -> char {
+        let i = rng.below(self.alphabet.chars().count() as u32) as usize;
         self.alphabet.chars().nth(i).unwrap()
     }
 }
@@ -219,7 +596,7 @@ mod tests {
     use std::collections::BTreeMap;
     use std::fs::File;
 
-    use rand::{rngs::StdRng, SeedableRng};
+    use rand::SeedableRng;
 
     fn get_language() -> Language {
         // Read and deserialize yaml file.
@@ -232,44 +609,68 @@ mod tests {
     #[test]
     fn generate_word_follows_rules() {
         let language = get_language();
-        let mut rng = StdRng::seed_from_u64(0);
+        let mut rng = Pcg32::seed_from_u64(0);
 
         for _ in 0..100 {
             let word = language.generate_word(&mut rng).expect("Failed to return word.");
             if word.contains("aa") || word.contains("bbb") || word.contains("cc") {
                 panic!("Word contained impossible pattern.")
             }
-            if word.len() < language.min as usize {
+            if word.len() < language.min {
                 panic!("Word impossibly short.")
             }
-            if word.len() > language.max as usize {
+            if word.len() > language.max {
                 panic!("Word impossibly long.")
             }
         }
 
     }
 
+    #[test]
+    fn score_correct_values() {
+        let language = get_language();
+
+        // Impossible patterns can't be generated, and neither can the last continuation of a pattern, like the rng.
+        assert_eq!(language.score("baa"), None);
+        assert_eq!(language.score("bbb"), None);
+        assert_eq!(language.score("bc"), None);
+        assert_eq!(language.score("d"), None);
+
+        // Possible words have a negative score, higher for more likely words.
+        let ba = language.score("ba").expect("Failed to score word.");
+        let bb = language.score("bb").expect("Failed to score word.");
+        assert!(ba < 0.0 && bb < 0.0);
+        assert!(ba > bb);
+    }
+
     #[test]
     fn replace_wildcards_does_not_replace_with_weight_0() {
         let language = get_language();
-        let mut rng = StdRng::seed_from_u64(0);
+        let mut rng = Pcg32::seed_from_u64(0);
 
         // Ensure a is never returned since it has weight 0.
         let map = &language.patterns.get("a").expect("YAML file missing pattern.").2;
         for _ in 0..100 {
-            let result = language.replace_wildcards(&mut rng, "_", map);
-            let result = result.as_str();
-            match result {
-                "a" => panic!("Impossible character returned."),
-                _ => (),
+            let result = language.replace_wildcards(&mut rng, "_", map).expect("Failed to replace wildcards.");
+            if result == "a" {
+                panic!("Impossible character returned.")
             }
         }
     }
 
+    #[test]
+    fn replace_wildcards_fails_without_letters_left() {
+        let language = get_language();
+        let mut rng = Pcg32::seed_from_u64(0);
+        let map = BTreeMap::from([(1, "a".to_owned()), (2, "b".to_owned()), (3, "c".to_owned()), (4, "_".to_owned())]);
+        assert!(language.replace_wildcards(&mut rng, "_", &map).is_err());
+        assert!(language.replace_wildcards(&mut rng, "_b", &map).is_ok_and(|r| r.len() == 2 && r.ends_with('b')));
+    }
+
     #[test]
     fn get_wildcard_returns_all_possibilities() {
         let language = get_language();
-        let mut rng = StdRng::seed_from_u64(0);
+        let mut rng = Pcg32::seed_from_u64(0);
 
         let mut a = false;
         let mut b = false;
@@ -294,7 +695,7 @@ mod tests {
         assert_eq!(inverse_lerp(0, 1, 1), 1.0);
         assert_eq!(inverse_lerp(0, 5, 3), 0.6);
         assert_eq!(inverse_lerp(5, 10, 7), 0.4);
-        assert_eq!(inverse_lerp(4, 7, 6), 0.66666666);
+        assert_eq!(inverse_lerp(4, 7, 6), 0.666_666_7);
     }
 
     #[test]
@@ -314,5 +715,21 @@ mod tests {
     fn inverse_lerp_left_greater_than_right() {
         inverse_lerp(1, 0, 0);
     }
+
+    #[test]
+    fn generate_words_at_equals_words_at_each_index() {
+        let yaml = File::open("./assets/testing/test1.yaml").expect("YAML file not found.");
+        let rules: BTreeMap<String, BTreeMap<String, u32>> = serde_yaml::from_reader(yaml).expect("YAML file wrong format.");
+
+        let words = generate_words_at(42, 4995, 10, &rules).expect("Failed to generate words.");
+        let each: Vec<String> = (4995..5005).map(|i| generate_word_at(42, i, &rules).expect("Failed to generate word.")).collect();
+        assert_eq!(words, each.join(" "));
+
+        // A range is the same words however it's split.
+        let first = generate_words_at(42, 4995, 4, &rules).expect("Failed to generate words.");
+        let second = generate_words_at(42, 4999, 6, &rules).expect("Failed to generate words.");
+        assert_eq!(words, format!("{first} {second}"));
+        assert_eq!(generate_words_at(42, 0, 0, &rules), Ok(String::new()));
+    }
 }
 // TESTS END
@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+
+// How much less often than expected two letters must appear in the same word for them to be in disharmony.
+const DISHARMONY_RATIO: f32 = 0.2;
+
+// How many words two letters are expected to appear in together before their tendency is reported.
+const MIN_EXPECTED: f32 = 5.0;
+
+// The harmony systems of a language, e.g. front and back vowels. Each system has sets of letters, and every letter
+// of a word in one of the sets must be in the same set as the first one. Letters at the same position in each set
+// correspond to each other, e.g. "aou | äöy" makes a and ä correspond. Letters in no set are neutral.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Harmony {
+    systems: Vec<Vec<Vec<char>>>,
+}
+
+// How often two letters of the same class appear in the same word, compared to how often they would by chance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tendency {
+    pub first: char,
+    pub second: char,
+    pub ratio: f32,
+}
+
+impl Harmony {
+    // Creates the harmony systems from the harmony section of the rules, where each system is written as its sets
+    // separated by "|", e.g. "aou | äöy: 0" for vowel harmony or "sz | ʃʒ: 0" for consonant harmony.
+    pub fn from_rules<'a>(rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Result<Self, &'a str> {
+        let mut systems = Vec::new();
+        for entry in rules.get("harmony").into_iter().flat_map(|h| h.keys()) {
+            let sets: Vec<Vec<char>> = entry.split('|').map(|s| s.chars().filter(|c| !c.is_whitespace()).collect()).collect();
+            if sets.len() < 2 || sets.iter().any(|s| s.is_empty()) {
+                return Err("Harmony is not in the form \"set | set\".")
+            }
+
+            let letters: Vec<char> = sets.iter().flatten().copied().collect();
+            if letters.iter().enumerate().any(|(i, c)| letters[..i].contains(c)) {
+                return Err("Harmony has a letter in several sets.")
+            }
+            systems.push(sets);
+        }
+        Ok(Self { systems })
+    }
+
+    // Gets every letter in the harmony sets.
+    pub fn letters(&self) -> impl Iterator<Item = &char> {
+        self.systems.iter().flatten().flatten()
+    }
+
+    // Makes a continuation agree with the first harmonic letter of the word, replacing each letter of the continuation
+    // in another set with its corresponding letter. Letters are only replaced if allowed, e.g. if the patterns know them.
+    pub fn harmonize(&self, word: &str, continuation: &str, allowed: impl Fn(char) -> bool) -> String {
+        let mut established: Vec<Option<usize>> = self.systems.iter().map(|s| first_set(s, word)).collect();
+
+        continuation.chars().map(|c| {
+            let mut result = c;
+            for (system, set) in self.systems.iter().zip(established.iter_mut()) {
+                let Some((index, position)) = find(system, c) else { continue };
+                match set {
+                    Some(s) if *s != index => {
+                        if let Some(replacement) = system[*s].get(position).filter(|r| allowed(**r)) {
+                            result = *replacement;
+                        }
+                    }
+                    Some(_) => (),
+                    None => *set = Some(index),
+                }
+            }
+            result
+        }).collect()
+    }
+
+    // Whether every harmonic letter of a word is in the same set as the first one, in every system.
+    pub fn is_harmonic(&self, word: &str) -> bool {
+        self.systems.iter().all(|system| {
+            let mut sets = word.chars().filter_map(|c| find(system, c).map(|(i, _)| i));
+            let first = sets.next();
+            sets.all(|s| Some(s) == first)
+        })
+    }
+}
+
+// Measures how often each pair of letters in the same group appears in the same word, compared to chance.
+// Only pairs expected in enough words are measured, from the least to the most often appearing together.
+pub fn tendencies(words: &[&str], groups: &[Vec<char>]) -> Vec<Tendency> {
+    let total = words.len() as f32;
+    let count = |letters: &[char]| words.iter().filter(|w| letters.iter().all(|l| w.contains(*l))).count() as f32;
+
+    let mut result = Vec::new();
+    for group in groups {
+        for (i, first) in group.iter().enumerate() {
+            for second in &group[i + 1..] {
+                let expected = count(&[*first]) * count(&[*second]) / total;
+                if expected < MIN_EXPECTED {
+                    continue
+                }
+                let ratio = count(&[*first, *second]) / expected;
+                result.push(Tendency { first: *first, second: *second, ratio });
+            }
+        }
+    }
+    result.sort_by(|a, b| a.ratio.partial_cmp(&b.ratio).unwrap());
+    result
+}
+
+// Suggests harmony systems, in the form of entries of the harmony section, from letters that avoid each other.
+// Letters in disharmony are put in opposite sets, and letters avoiding each other the most correspond to each other.
+pub fn systems_from_tendencies(tendencies: &[Tendency]) -> Vec<String> {
+    let avoiding: Vec<&Tendency> = tendencies.iter().filter(|t| t.ratio < DISHARMONY_RATIO).collect();
+
+    // Split the letters into two sets, so that letters in disharmony are in opposite sets.
+    let mut sides: BTreeMap<char, bool> = BTreeMap::new();
+    let mut systems: Vec<Vec<char>> = Vec::new();
+    for tendency in &avoiding {
+        if sides.contains_key(&tendency.first) || sides.contains_key(&tendency.second) {
+            continue
+        }
+        let mut system = vec![tendency.first];
+        sides.insert(tendency.first, false);
+
+        // Add every letter connected to the system, unless it would be in disharmony with its own set.
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for t in &avoiding {
+                let (known, new) = match (sides.get(&t.first), sides.get(&t.second)) {
+                    (Some(s), None) if system.contains(&t.first) => (*s, t.second),
+                    (None, Some(s)) if system.contains(&t.second) => (*s, t.first),
+                    _ => continue,
+                };
+                let conflict = avoiding.iter().any(|o| {
+                    let other = if o.first == new { o.second } else if o.second == new { o.first } else { return false };
+                    system.contains(&other) && sides[&other] != known
+                });
+                if !conflict {
+                    sides.insert(new, !known);
+                    system.push(new);
+                    changed = true;
+                }
+            }
+        }
+        systems.push(system);
+    }
+
+    // Pair up the letters of the two sets, from the pair avoiding each other the most.
+    systems.into_iter().map(|system| {
+        let (mut first, mut second) = (Vec::new(), Vec::new());
+        for t in &avoiding {
+            let both = system.contains(&t.first) && system.contains(&t.second);
+            if both && sides[&t.first] != sides[&t.second] && !first.contains(&t.first) && !first.contains(&t.second)
+                && !second.contains(&t.first) && !second.contains(&t.second) {
+                let (a, b) = if sides[&t.first] { (t.second, t.first) } else { (t.first, t.second) };
+                first.push(a);
+                second.push(b);
+            }
+        }
+        for c in &system {
+            if !first.contains(c) && !second.contains(c) {
+                if sides[c] { second.push(*c) } else { first.push(*c) }
+            }
+        }
+        format!("{} | {}", first.iter().collect::<String>(), second.iter().collect::<String>())
+    }).collect()
+}
+
+// Finds the index of the set a letter is in, tupled with its position in that set.
+fn find(system: &[Vec<char>], c: char) -> Option<(usize, usize)> {
+    system.iter().enumerate().find_map(|(i, set)| set.iter().position(|l| *l == c).map(|p| (i, p)))
+}
+
+// Finds the index of the set of the first harmonic letter of a word.
+fn first_set(system: &[Vec<char>], word: &str) -> Option<usize> {
+    word.chars().find_map(|c| find(system, c).map(|(i, _)| i))
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_harmony(systems: &[&str]) -> Harmony {
+        let rules = BTreeMap::from([("harmony".to_owned(), systems.iter().map(|s| (s.to_string(), 0)).collect())]);
+        Harmony::from_rules(&rules).expect("Failed to read harmony.")
+    }
+
+    #[test]
+    fn harmonize_correct_values() {
+        let harmony = get_harmony(&["aou | äöy", "s | ʃ"]);
+        assert_eq!(harmony.harmonize(" täm", "a", |_| true), "ä");
+        assert_eq!(harmony.harmonize(" tem", "ossa", |_| true), "ossa");
+        assert_eq!(harmony.harmonize(" ", "kylä", |_| true), "kylä");
+        assert_eq!(harmony.harmonize(" ʃy", "sus", |_| true), "ʃyʃ");
+        assert_eq!(harmony.harmonize(" ʃy", "sus", |c| c != 'ʃ'), "sys");
+    }
+
+    #[test]
+    fn is_harmonic_correct_values() {
+        let harmony = get_harmony(&["aou | äöy"]);
+        assert!(harmony.is_harmonic("talossa"));
+        assert!(harmony.is_harmonic("kylässä"));
+        assert!(harmony.is_harmonic("tie"));
+        assert!(!harmony.is_harmonic("talossä"));
+    }
+
+    #[test]
+    fn from_rules_invalid_harmony() {
+        for system in ["aou", "aou | ", "aou | äöa"] {
+            let rules = BTreeMap::from([("harmony".to_owned(), BTreeMap::from([(system.to_owned(), 0)]))]);
+            assert!(Harmony::from_rules(&rules).is_err(), "{system}");
+        }
+    }
+
+    #[test]
+    fn systems_from_tendencies_correct_values() {
+        // a and u avoid ä, y and ö, but a avoids y and u avoids ä less than their corresponding letters do.
+        let mut words: Vec<&str> = ["ta", "tu", "tau", "tua", "tä", "ty", "täy", "työ", "to", "töt"]
+            .iter().flat_map(|w| std::iter::repeat_n(*w, 10)).collect();
+        words.extend(["tay", "tuä"]);
+        let tendencies = tendencies(&words, &[vec!['a', 'o', 'u', 'y', 'ä', 'ö']]);
+        assert!(tendencies.iter().any(|t| t.first == 'a' && t.second == 'ä' && t.ratio == 0.0));
+        assert_eq!(systems_from_tendencies(&tendencies), ["au | äyö"]);
+    }
+}
+// TESTS END
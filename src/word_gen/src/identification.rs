@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+
+use crate::generator::Language;
+use crate::{reader, verification};
+
+// The probability of each continuation of a word a language can't generate, so a single foreign-looking word
+// makes a language unlikely instead of ruling it out.
+const UNLIKELY: f32 = 0.001;
+
+// Identifies which of the languages of the rules a text is written in, returning the confidence of each language,
+// in the same order, as the probability of it generating the words of the text relative to the others.
+// The confidences add up to 1.
+pub fn identify<'a>(text: &str, models: &[&BTreeMap<String, BTreeMap<String, u32>>]) -> Result<Vec<f32>, &'a str> {
+    let languages = get_languages(models)?;
+    let text = reader::filter_string(text);
+    if text.is_empty() {
+        return Err("No words to identify.")
+    }
+
+    let likelihoods: Vec<f32> = languages.iter().map(|l| text.split(' ').map(|w| log_likelihood(l, w)).sum()).collect();
+    Ok(confidences(&likelihoods))
+}
+
+// Identifies the language of each word of a text, returning each word tupled with the confidence of each language.
+pub fn identify_words<'a>(text: &str, models: &[&BTreeMap<String, BTreeMap<String, u32>>]) -> Result<Vec<(String, Vec<f32>)>, &'a str> {
+    let languages = get_languages(models)?;
+    let text = reader::filter_string(text);
+
+    Ok(text.split_whitespace().map(|w| {
+        let likelihoods: Vec<f32> = languages.iter().map(|l| log_likelihood(l, w)).collect();
+        (w.to_owned(), confidences(&likelihoods))
+    }).collect())
+}
+
+// Reports which of the languages of the rules, with their names, a text is written in: the confidence of each
+// language from the most to the least likely, followed by the most likely language of each word if there are several.
+pub fn report<'a>(text: &str, models: &[&BTreeMap<String, BTreeMap<String, u32>>], names: &[&str]) -> Result<String, &'a str> {
+    if names.len() != models.len() {
+        return Err("Every language to identify needs a name.")
+    }
+
+    let mut ranked: Vec<(&str, f32)> = names.iter().copied().zip(identify(text, models)?).collect();
+    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    let mut lines: Vec<String> = ranked.iter().map(|(name, confidence)| format!("{name}: {:.1}%", confidence * 100.0)).collect();
+
+    let words = identify_words(text, models)?;
+    if words.len() > 1 {
+        for (word, confidences) in words {
+            let (best, confidence) = confidences.iter().enumerate().max_by(|a, b| a.1.partial_cmp(b.1).unwrap()).unwrap();
+            lines.push(format!("  {word}: {} ({:.1}%)", names[best], confidence * 100.0));
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+// Verifies the rules and sets up their languages.
+fn get_languages<'a>(models: &[&BTreeMap<String, BTreeMap<String, u32>>]) -> Result<Vec<Language>, &'a str> {
+    if models.len() < 2 {
+        return Err("Identification needs at least 2 languages.")
+    }
+    for rules in models {
+        if verification::verify_rules(rules).is_err() {
+            return Err("Rules to identify with are not valid.")
+        }
+    }
+    Ok(models.iter().map(|r| Language::new(r)).collect())
+}
+
+// Gets the natural logarithm of the probability of a language generating a word, or of it being very unlikely
+// for every letter and the ending if the language can't generate it.
+fn log_likelihood(language: &Language, word: &str) -> f32 {
+    language.log_likelihood(word).unwrap_or(UNLIKELY.ln() * (word.chars().count() + 1) as f32)
+}
+
+// Turns the natural logarithms of the likelihoods into confidences that add up to 1 (softmax).
+fn confidences(likelihoods: &[f32]) -> Vec<f32> {
+    let max = likelihoods.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let exps: Vec<f32> = likelihoods.iter().map(|l| (l - max).exp()).collect();
+    let sum: f32 = exps.iter().sum();
+    exps.iter().map(|e| e / sum).collect()
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+
+    fn get_rules(name: &str) -> BTreeMap<String, BTreeMap<String, u32>> {
+        let yaml = File::open(format!("./assets/testing/{name}.yaml")).expect("YAML file not found.");
+        serde_yaml::from_reader(yaml).expect("YAML file wrong format.")
+    }
+
+    #[test]
+    fn confidences_correct_values() {
+        assert_eq!(confidences(&[0.0, 0.0]), [0.5, 0.5]);
+        let result = confidences(&[-1.0, -1.0 - 3f32.ln()]);
+        assert!((result[0] - 0.75).abs() < 1e-6 && (result[1] - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn identify_words_correct_values() {
+        let first = get_rules("test1");
+        let second = get_rules("test15");
+        let result = identify_words("Baba, cab!", &[&first, &second]).expect("Failed to identify words.");
+        assert_eq!(result.iter().map(|(w, _)| w.as_str()).collect::<Vec<&str>>(), ["baba", "cab"]);
+        assert!(result.iter().all(|(_, c)| c[0] > 0.5 && (c.iter().sum::<f32>() - 1.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn report_correct_values() {
+        let first = get_rules("test1");
+        let second = get_rules("test15");
+        let result = report("Baba, cab!", &[&first, &second], &["test1", "test15"]).expect("Failed to report.");
+        let lines: Vec<&str> = result.lines().collect();
+        assert!(lines[0].starts_with("test1: ") && lines[1].starts_with("test15: "));
+        assert!(lines[2].starts_with("  baba: test1 (") && lines[3].starts_with("  cab: test1 ("));
+        assert!(report("ba", &[&first, &second], &["test1"]).is_err());
+    }
+
+    #[test]
+    fn identify_invalid_values() {
+        let rules = get_rules("test1");
+        assert!(identify("ba", &[&rules]).is_err());
+        assert!(identify("123", &[&rules, &rules]).is_err());
+        assert!(identify("ba", &[&rules, &BTreeMap::new()]).is_err());
+    }
+}
+// TESTS END
@@ -1,4 +1,28 @@
+pub mod blend;
+pub mod classes;
 pub mod command;
+pub mod comparison;
+pub mod continuum;
+pub mod extraction;
+pub mod family;
 pub mod generator;
+pub mod harmony;
+pub mod identification;
+pub mod naming;
+pub mod nativization;
+pub mod orthography;
+pub mod output;
+pub mod paths;
+pub mod pronunciation;
+pub mod phonology;
+pub mod prosody;
+pub mod provenance;
 pub mod reader;
+pub mod rng;
+pub mod seed;
+pub mod sound_change;
+pub mod statistics;
+pub mod syllable;
+pub mod template;
+pub mod trace;
 pub mod verification;
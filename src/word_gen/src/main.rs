@@ -1,17 +1,239 @@
-use rand::{rngs::StdRng, SeedableRng};
+use std::collections::BTreeSet;
+use std::path::Path;
 
-use word_gen::{command, generator};
+use rand::SeedableRng;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let rules = command::get_rules(true)?;
+use word_gen::naming::Namer;
+use word_gen::output::{self, OutputFormat};
+use word_gen::prosody::Prosody;
+use word_gen::rng::{Pcg32, ALGORITHM, ALGORITHM_VERSION};
+use word_gen::seed::SeedNode;
+use word_gen::{blend, command, comparison, generator, identification, orthography, phonology, seed, statistics, template, verification};
 
-    // Create the rng from a seed.
-    let seed = rand::random::<u64>();
-    println!("Used seed: {seed}");
-    let mut rng = StdRng::seed_from_u64(seed);
+fn main() {
+    // Print out errors with their messages, e.g. every location tried for a file that wasn't found.
+    if let Err(e) = run() {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    // Parse the application arguments once, and search for files given by name in the directories they add.
+    let matches = command::cli().get_matches();
+    let resolver = command::resolver(&matches);
+
+    // Print out each word before and after the sound changes, if they were given instead of generating words.
+    if let Some((changes, words, rules)) = command::get_change_paths(&matches) {
+        for (before, after) in command::change_words(&changes, &words, rules.as_deref(), &resolver)? {
+            println!("{before} > {after}");
+        }
+        return Ok(())
+    }
+
+    // Print out each foreign word before and after adapting it to the language, if they were given instead of generating words.
+    if let Some((words, rules)) = command::get_nativize_paths(&matches) {
+        for (before, after) in command::nativize_words(&words, &rules, &resolver)? {
+            println!("{before} > {after}");
+        }
+        return Ok(())
+    }
+
+    // Print out the confidence of each language for the text, and the most likely language of each of its words,
+    // if the identify command was given instead of generating words.
+    if let Some((models, text)) = command::get_identification(&matches) {
+        let rules = models.iter().map(|path| command::read_rules(path, &resolver)).collect::<Result<Vec<_>, _>>()?;
+        let rules: Vec<_> = rules.iter().collect();
+        let names: Vec<String> = models.iter()
+            .map(|m| Path::new(m).file_stem().map_or(m.to_owned(), |s| s.to_string_lossy().into_owned()))
+            .collect();
+        let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+        println!("{}", identification::report(&text, &rules, &names)?);
+        return Ok(())
+    }
+
+    // Print out a report of the differences between two rules, if the compare command was given instead of generating words.
+    if let Some((first, second)) = command::get_compare_paths(&matches) {
+        let comparison = comparison::compare_rules(&command::read_rules(&first, &resolver)?, &command::read_rules(&second, &resolver)?)?;
+        println!("{}", comparison.report(&first, &second));
+        return Ok(())
+    }
+
+    // Print out whether each rules file is valid, failing if any of them is not.
+    if let Some(paths) = command::get_verify_paths(&matches) {
+        let mut valid = true;
+        for path in paths {
+            let result = command::read_rules(&path, &resolver).map_err(|e| e.to_string())
+                .and_then(|rules| verification::verify_rules(&rules).map_err(|e| e.to_owned()));
+            match result {
+                Ok(()) => println!("{path}: valid"),
+                Err(e) => {
+                    println!("{path}: {e}");
+                    valid = false;
+                }
+            }
+        }
+        return if valid { Ok(()) } else { Err("Some rules are not valid.".into()) }
+    }
+
+    // Print out the score of each word, or that the language can't generate it.
+    if let Some((rules, words)) = command::get_score(&matches) {
+        let rules = command::read_rules(&rules, &resolver)?;
+        let words: Vec<&str> = words.iter().map(|w| w.as_str()).collect();
+        for (word, score) in generator::score_words(&words, &rules)? {
+            match score {
+                Some(s) => println!("{word}\t{s:.3}"),
+                None => println!("{word}\timpossible"),
+            }
+        }
+        return Ok(())
+    }
+
+    // Print out the name of each key, unique among the keys if wanted.
+    if let Some((rules, keys, salt, unique)) = command::get_naming(&matches) {
+        let rules = command::read_rules(&rules, &resolver)?;
+        let namer = Namer::from_rules(&rules, &salt)?;
+        let keys: Vec<&str> = keys.iter().map(|k| k.as_str()).collect();
+        let names = if unique {
+            namer.names_for(&keys, &BTreeSet::new())?
+        } else {
+            keys.iter().map(|k| namer.name_for(k)).collect::<Result<Vec<_>, _>>()?
+        };
+        for (key, name) in keys.iter().zip(names) {
+            println!("{key}\t{name}");
+        }
+        return Ok(())
+    }
+
+    // Learn and write out the rules, if the learn command was given instead of generating words.
+    if command::learn(&matches, &resolver)? {
+        return Ok(())
+    }
+
+    // Save or print out the plain text of a sample text, if the convert command was given.
+    if let Some((sample, format, output)) = command::get_conversion(&matches) {
+        let text = command::convert_sample(&sample, format.as_deref())?;
+        match output {
+            Some(path) => command::save_text(&text, &path)?,
+            None => println!("{text}"),
+        }
+        return Ok(())
+    }
+
+    // Create the rng from a seed, random unless one was given, and print out the version of its algorithm
+    // and how to run again with the same seed, to stderr if the words are printed in a machine-readable format.
+    let format = command::get_output_format(&matches);
+    let report = |line: String| if format == OutputFormat::Plain { println!("{line}") } else { eprintln!("{line}") };
+    let seed = command::get_seed(&matches).unwrap_or_else(rand::random::<u64>);
+    report(format!("Used seed: {seed} ({ALGORITHM} version {ALGORITHM_VERSION})"));
+    report(format!("Replay: {}", seed::replay_command(&std::env::args().collect::<Vec<String>>(), seed)));
+
+    // Use the seed at the path in the tree of seeds derived from the seed if one was given, e.g. of a town in a region of a world.
+    let seed = match command::get_seed_path(&matches) {
+        Some(path) => {
+            let node = SeedNode::root(seed).descend(&path)?;
+            report(format!("Seed of {path}: {}", node.seed));
+            node.seed
+        }
+        None => seed,
+    };
+    let mut rng = Pcg32::seed_from_u64(seed);
+
+    // Print out the statistics of the rules and of a sample of generated words, if the stats command was given.
+    if let Some((rules, amount)) = command::get_statistics(&matches) {
+        let rules = command::read_rules(&rules, &resolver)?;
+        println!("{}", statistics::rules_statistics(&mut rng, amount, &rules)?);
+        return Ok(())
+    }
+
+    // Print out the sound changes of each daughter language and the table of cognates, if a family was generated.
+    if let Some(family) = command::get_family(&matches, &resolver, seed)? {
+        for daughter in &family.daughters {
+            println!("{}: {}", daughter.name, daughter.changes.join(", "));
+        }
+        let names: Vec<&str> = family.daughters.iter().map(|d| d.name.as_str()).collect();
+        println!("proto\t{}", names.join("\t"));
+        for row in family.cognates() {
+            println!("{}", row.join("\t"));
+        }
+        return Ok(())
+    }
+
+    // Use the rules of a brand-new language if one was invented, or the blend of several rules if they were blended,
+    // otherwise read or learn them. New rules are saved, or printed out before the words if no output was given.
+    let (output, force) = command::get_new_rules_output(&matches);
+    let rules = if let Some(phonology) = command::get_phonology(&matches) {
+        let rules = phonology::generate_rules(&mut rng, &phonology)?;
+        command::write_new_rules(&rules, "Invented", Some(seed), output.as_deref(), force)?;
+        rules
+    } else if let Some(models) = command::get_blend(&matches) {
+        let blended = models.iter().map(|(path, _)| command::read_rules(path, &resolver)).collect::<Result<Vec<_>, _>>()?;
+        let weights: Vec<u32> = models.iter().map(|(_, weight)| *weight).collect();
+        let rules = blend::blend_rules(&blended.iter().collect::<Vec<_>>(), &weights)?;
+        command::write_new_rules(&rules, "Blended", None, output.as_deref(), force)?;
+        rules
+    } else {
+        command::get_rules(&matches, &resolver)?
+    };
+    let (syllables, hyphenate) = command::get_syllables(&matches);
+
+    // Print out how each word was generated instead of the words, if they should be explained.
+    let count = command::get_count(&matches);
+    let index = command::get_index(&matches);
+    if command::get_explain(&matches) {
+        let traces = match index {
+            Some(i) => generator::explain_words_at(seed, i, count, syllables, &rules)?,
+            None => generator::explain_words(&mut rng, count, syllables, &rules)?,
+        };
+        for trace in traces {
+            println!("{trace}\n");
+        }
+        return Ok(())
+    }
+
+    // Print out the record of each word instead, if the words should be printed in a machine-readable format.
+    // The words are the same as the plain ones, with their seed and index if they were generated by index.
+    if format != OutputFormat::Plain {
+        let records = match index {
+            Some(i) => generator::generate_records_at(seed, i, count, syllables, &rules)?,
+            None => generator::generate_records(&mut rng, count, syllables, &rules)?,
+        };
+        println!("{}", output::format_records(&records, format));
+        return Ok(())
+    }
+
+    // Generate the words (10 by default), from the syllable templates (2 syllables by default) if wanted,
+    // otherwise aiming for the number of syllables if it was given, and from an index of the words for the seed if given.
+    let words = match (command::get_templates(&matches), syllables, index) {
+        (Some(c), s, _) => template::generate_words_from_templates(&mut rng, count, s.unwrap_or(2), &rules, c)?,
+        (None, Some(s), Some(i)) => generator::generate_words_at_with_syllables(seed, i, count, s, &rules)?,
+        (None, Some(s), None) => generator::generate_words_with_syllables(&mut rng, count, s, &rules)?,
+        (None, None, Some(i)) => generator::generate_words_at(seed, i, count, &rules)?,
+        (None, None, None) => generator::generate_words(&mut rng, count, &rules)?,
+    };
+
+    // Render the words with the stress and tones of the rules, hyphenated and with their syllable count if wanted.
+    let prosody = Prosody::from_rules(&rules)?;
+    let display = |word: &str| if hyphenate {
+        format!("{} ({})", prosody.render(word, "-"), prosody.annotate(word).len())
+    } else {
+        prosody.render(word, "")
+    };
 
-    // Print out 10 words.
-    println!("{}", generator::generate_words(&mut rng, 10, &rules)?);
+    // Print out the words, along with their spellings in each orthography if the rules have any.
+    let orthographies = orthography::all(&rules)?;
+    if orthographies.is_empty() {
+        let words: Vec<String> = words.split(' ').map(display).collect();
+        println!("{}", words.join(" "));
+    } else {
+        for word in words.split(' ') {
+            let spellings: Vec<String> = orthographies.iter().map(|(name, o)| match name {
+                Some(n) => format!("{n}: {}", o.spell(word)),
+                None => o.spell(word),
+            }).collect();
+            println!("/{}/ {}", display(word), spellings.join(", "));
+        }
+    }
 
     Ok(())
 }
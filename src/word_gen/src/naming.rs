@@ -0,0 +1,111 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use rand::SeedableRng;
+
+use crate::generator::Language;
+use crate::rng::Pcg32;
+use crate::{seed, verification};
+
+// The number of names tried for a key before giving up on finding one that isn't taken.
+const ATTEMPTS: u64 = 1000;
+
+// Names entities by keys, e.g. "npc:1234", where a key always gets the same name from the same rules and salt,
+// whatever order the names are asked for in.
+pub struct Namer {
+    language: Language,
+    pub salt: String,
+}
+
+impl Namer {
+    // Creates the namer of rules, with a salt that gives the same keys other names, e.g. the name of a world.
+    pub fn from_rules<'a>(rules: &'a BTreeMap<String, BTreeMap<String, u32>>, salt: &str) -> Result<Self, &'a str> {
+        verification::verify_rules(rules)?;
+        Ok(Self { language: Language::new(rules), salt: salt.to_owned() })
+    }
+
+    // Gets the seed of a key, hashed from the salt and the key.
+    pub fn seed_for(&self, key: &str) -> u64 {
+        seed::seed_from_phrase(&format!("{}\0{key}", self.salt))
+    }
+
+    // Gets the name of a key.
+    pub fn name_for(&self, key: &str) -> Result<String, &str> {
+        self.name_for_avoiding(key, &BTreeSet::new())
+    }
+
+    // Gets the name of a key that isn't one of the taken words. It's the name of name_for unless that's taken,
+    // otherwise the first of the other names of the key that isn't, so a key keeps its name while the same words are taken.
+    pub fn name_for_avoiding(&self, key: &str, taken: &BTreeSet<String>) -> Result<String, &str> {
+        let seed = self.seed_for(key);
+        for attempt in 0..ATTEMPTS {
+            let mut rng = Pcg32::seed_from_u64(seed::index_seed(seed, attempt));
+            let name = self.language.generate_word(&mut rng)?;
+            if !taken.contains(&name) {
+                return Ok(name)
+            }
+        }
+        Err("Couldn't find a name that isn't taken.")
+    }
+
+    // Gets the names of keys in order, where each name isn't one of the taken words or the name of a key before it.
+    pub fn names_for(&self, keys: &[&str], taken: &BTreeSet<String>) -> Result<Vec<String>, &str> {
+        let mut taken = taken.clone();
+        let mut result = Vec::new();
+        for key in keys {
+            let name = self.name_for_avoiding(key, &taken)?;
+            taken.insert(name.clone());
+            result.push(name);
+        }
+        Ok(result)
+    }
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+
+    fn get_rules() -> BTreeMap<String, BTreeMap<String, u32>> {
+        let yaml = File::open("./assets/testing/test15.yaml").expect("YAML file not found.");
+        serde_yaml::from_reader(yaml).expect("YAML file wrong format.")
+    }
+
+    #[test]
+    fn name_for_correct_values() {
+        let rules = get_rules();
+        let namer = Namer::from_rules(&rules, "world").expect("Failed to create namer.");
+        let name = namer.name_for("npc:1234").expect("Failed to get name.");
+
+        // The same key gets the same name, whatever was named before it.
+        let _ = namer.name_for("npc:1").expect("Failed to get name.");
+        assert_eq!(namer.name_for("npc:1234"), Ok(name.clone()));
+        assert_eq!(Namer::from_rules(&rules, "world").unwrap().name_for("npc:1234"), Ok(name));
+        assert_ne!(namer.seed_for("npc:1234"), Namer::from_rules(&rules, "other").unwrap().seed_for("npc:1234"));
+    }
+
+    #[test]
+    fn name_for_avoiding_skips_taken() {
+        let rules = get_rules();
+        let namer = Namer::from_rules(&rules, "").expect("Failed to create namer.");
+        let name = namer.name_for("town").expect("Failed to get name.");
+
+        let taken = BTreeSet::from([name.clone()]);
+        let other = namer.name_for_avoiding("town", &taken).expect("Failed to get name.");
+        assert_ne!(other, name);
+        assert_eq!(namer.name_for_avoiding("town", &taken), Ok(other));
+    }
+
+    #[test]
+    fn names_for_are_distinct() {
+        let rules = get_rules();
+        let namer = Namer::from_rules(&rules, "").expect("Failed to create namer.");
+        let keys: Vec<String> = (0..30).map(|i| format!("npc:{i}")).collect();
+        let keys: Vec<&str> = keys.iter().map(|k| k.as_str()).collect();
+        let names = namer.names_for(&keys, &BTreeSet::new()).expect("Failed to get names.");
+        assert_eq!(names.iter().collect::<BTreeSet<_>>().len(), 30);
+        assert_eq!(names[0], namer.name_for("npc:0").unwrap());
+    }
+}
+// TESTS END
@@ -0,0 +1,137 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::generator::Language;
+use crate::verification;
+
+// How many of the closest candidates are kept to edit further at each edit distance.
+const BEAM_WIDTH: usize = 64;
+
+// How many edits more than the length of the word are tried before giving up.
+const EXTRA_EDITS: usize = 2;
+
+// Adapts a foreign word to the language of the rules, e.g. "computer", by finding the word the language can
+// generate with the fewest letters substituted, deleted or inserted, and the highest score among those.
+// Letters outside the alphabet are replaced, and patterns the language can't generate are repaired.
+pub fn nativize_word<'a>(word: &str, rules: &'a BTreeMap<String, BTreeMap<String, u32>>) -> Result<String, &'a str> {
+    verification::verify_rules(rules)?;
+    let language = Language::new(rules);
+    let alphabet: Vec<char> = rules["alphabet"].first_key_value().unwrap().0.chars().collect();
+    nativize(&language, &alphabet, word).ok_or("No native word found.")
+}
+
+// Adapts each of the foreign words to the language of the rules, returning each word tupled with its native form.
+pub fn nativize_words<'a>(words: &[&str], rules: &'a BTreeMap<String, BTreeMap<String, u32>>) -> Result<Vec<(String, String)>, &'a str> {
+    verification::verify_rules(rules)?;
+    let language = Language::new(rules);
+    let alphabet: Vec<char> = rules["alphabet"].first_key_value().unwrap().0.chars().collect();
+    words.iter().map(|w| {
+        let native = nativize(&language, &alphabet, w).ok_or("No native word found.")?;
+        Ok((w.to_string(), native))
+    }).collect()
+}
+
+// Searches the words at each edit distance from the word in turn, keeping only the ones the language can generate
+// the longest start of to edit further, until some of them can be generated entirely.
+fn nativize(language: &Language, alphabet: &[char], word: &str) -> Option<String> {
+    let word = word.trim().to_lowercase();
+    if word.is_empty() {
+        return None
+    }
+
+    let mut seen = BTreeSet::from([word.clone()]);
+    let mut frontier = vec![word.clone()];
+    if language.score(&word).is_some() {
+        return Some(word)
+    }
+
+    for _ in 0..word.chars().count() + EXTRA_EDITS {
+        let mut candidates: Vec<String> = frontier.iter()
+            .flat_map(|w| edits(w, alphabet))
+            .filter(|w| !w.is_empty() && seen.insert(w.to_owned()))
+            .collect();
+
+        // The most likely of the words the language can generate is the native word.
+        let best = candidates.iter()
+            .filter_map(|w| language.score(w).map(|s| (s, w)))
+            .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        if let Some((_, native)) = best {
+            return Some(native.to_owned())
+        }
+
+        // Keep editing the words the language can generate the most of, and most likely.
+        let mut ranked: Vec<((usize, f32), String)> = candidates.drain(..).map(|w| (language.score_start(&w), w)).collect();
+        ranked.sort_by(|a, b| b.0.0.cmp(&a.0.0).then(b.0.1.partial_cmp(&a.0.1).unwrap()).then(a.1.cmp(&b.1)));
+        frontier = ranked.into_iter().take(BEAM_WIDTH).map(|(_, w)| w).collect();
+    }
+    None
+}
+
+// Gets every word one substitution, deletion or insertion of a letter of the alphabet away from the word.
+fn edits(word: &str, alphabet: &[char]) -> Vec<String> {
+    let chars: Vec<char> = word.chars().collect();
+    let mut result = Vec::new();
+    for i in 0..=chars.len() {
+        let (before, after) = chars.split_at(i);
+        let before: String = before.iter().collect();
+        for c in alphabet {
+            result.push(format!("{before}{c}{}", after.iter().collect::<String>()));
+        }
+        if let Some((first, rest)) = after.split_first() {
+            let rest: String = rest.iter().collect();
+            result.push(format!("{before}{rest}"));
+            for c in alphabet.iter().filter(|c| *c != first) {
+                result.push(format!("{before}{c}{rest}"));
+            }
+        }
+    }
+    result
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+
+    fn get_rules() -> BTreeMap<String, BTreeMap<String, u32>> {
+        let yaml = File::open("./assets/testing/test1.yaml").expect("YAML file not found.");
+        serde_yaml::from_reader(yaml).expect("YAML file wrong format.")
+    }
+
+    #[test]
+    fn edits_correct_values() {
+        let mut result = edits("ab", &['a', 'c']);
+        result.sort();
+        result.dedup();
+        assert_eq!(result, ["a", "aa", "aab", "aba", "abc", "ac", "acb", "b", "cab", "cb"]);
+    }
+
+    #[test]
+    fn nativize_word_keeps_native_words() {
+        let rules = get_rules();
+        assert_eq!(nativize_word("ba", &rules), Ok("ba".to_owned()));
+    }
+
+    #[test]
+    fn nativize_word_repairs_words() {
+        let rules = get_rules();
+        let language = Language::new(&rules);
+
+        // Letters outside the alphabet and impossible patterns are replaced with the fewest edits.
+        for word in ["bd", "baa", "Cab", "xyz"] {
+            let native = nativize_word(word, &rules).expect("Failed to nativize word.");
+            assert!(language.score(&native).is_some(), "{word} > {native}");
+        }
+        let native = nativize_word("bd", &rules).expect("Failed to nativize word.");
+        assert!(edits("bd", &['a', 'b', 'c']).contains(&native));
+    }
+
+    #[test]
+    fn nativize_word_invalid_values() {
+        let rules = get_rules();
+        assert!(nativize_word("", &rules).is_err());
+        assert!(nativize_word("ba", &BTreeMap::new()).is_err());
+    }
+}
+// TESTS END
@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+
+use crate::seed;
+
+// A single element of a context: a character, a set of characters like "[ei]", or the word boundary "#".
+#[derive(Debug, Clone, PartialEq)]
+enum Element {
+    Char(char),
+    Set(Vec<char>),
+    Boundary,
+}
+
+// One of the spellings of a unit, only used when the unit is surrounded by its context, if it has one.
+#[derive(Debug, Clone, PartialEq)]
+struct Spelling {
+    spelling: String,
+    before: Vec<Element>,
+    after: Vec<Element>,
+    contextual: bool,
+    weight: u32,
+}
+
+// The orthography maps units, e.g. phonemes, to their spellings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Orthography {
+    units: BTreeMap<String, Vec<Spelling>>,
+    longest: usize,
+}
+
+impl Orthography {
+    // Creates the orthography from one of the orthography sections of the rules, the unnamed "orthography" if name is None.
+    // Each entry is written as "unit > spelling: weight", or "unit > spelling / context: weight" when the spelling
+    // is only used in a context, e.g. "k > c / _[ei]" for /k/ before e or i, or "k > q / #_" at the start of a word.
+    pub fn from_rules<'a>(rules: &BTreeMap<String, BTreeMap<String, u32>>, name: Option<&str>) -> Result<Self, &'a str> {
+        let section = match name {
+            Some(n) => rules.get(&format!("orthography:{n}")).ok_or("No such orthography in rules.")?,
+            None => rules.get("orthography").ok_or("No orthography in rules.")?,
+        };
+        Self::from_section(section)
+    }
+
+    // Creates the orthography from the entries of an orthography section.
+    fn from_section<'a>(section: &BTreeMap<String, u32>) -> Result<Self, &'a str> {
+        let mut units: BTreeMap<String, Vec<Spelling>> = BTreeMap::new();
+        for (entry, weight) in section {
+            let (rule, context) = match entry.split_once(" / ") {
+                Some((r, c)) => (r, Some(c)),
+                None => (entry.as_str(), None),
+            };
+            let (unit, spelling) = match rule.split_once(" > ") {
+                Some((u, s)) if !u.is_empty() => (u, s),
+                _ => return Err("Orthography entry is not in the form \"unit > spelling\"."),
+            };
+            let (before, after) = match context {
+                Some(c) => {
+                    let (b, a) = c.split_once('_').ok_or("Orthography context has no \"_\".")?;
+                    (parse_context(b)?, parse_context(a)?)
+                }
+                None => (vec![], vec![]),
+            };
+            units.entry(unit.to_owned()).or_default().push(Spelling {
+                spelling: spelling.to_owned(),
+                before,
+                after,
+                contextual: context.is_some(),
+                weight: *weight,
+            });
+        }
+
+        let longest = units.keys().map(|u| u.chars().count()).max().unwrap_or(0);
+        Ok(Self { units, longest })
+    }
+
+    // Gets every unit that has a spelling.
+    pub fn units(&self) -> impl Iterator<Item = &String> {
+        self.units.keys()
+    }
+
+    // Spells a word, always matching the longest unit first. Characters without a spelling are kept as they are.
+    // Spellings in a matching context take precedence over those without one, and when a unit still has several
+    // spellings, one is picked by weight based on a hash of the word. This way a word is always spelled the same,
+    // without using the rng that generated the word.
+    pub fn spell(&self, word: &str) -> String {
+        let chars: Vec<char> = word.chars().collect();
+        let mut result = String::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let mut matched = false;
+            for length in (1..=self.longest.min(chars.len() - i)).rev() {
+                let unit: String = chars[i..i + length].iter().collect();
+                let spellings = match self.units.get(&unit) {
+                    Some(s) => s,
+                    None => continue,
+                };
+
+                // Prefer the spellings whose context matches, if any.
+                let in_context: Vec<&Spelling> = spellings.iter()
+                    .filter(|s| s.contextual && matches_before(&s.before, &chars[..i]) && matches_after(&s.after, &chars[i + length..]))
+                    .collect();
+                let candidates = if in_context.is_empty() {
+                    spellings.iter().filter(|s| !s.contextual).collect()
+                } else {
+                    in_context
+                };
+
+                // The position is hashed as a u64, so the word is spelled the same on every platform.
+                if let Some(spelling) = pick(&candidates, seed::fnv1a(word.bytes().chain((i as u64).to_le_bytes()))) {
+                    result.push_str(&spelling.spelling);
+                    i += length;
+                    matched = true;
+                    break
+                }
+            }
+            if !matched {
+                result.push(chars[i]);
+                i += 1;
+            }
+        }
+        result
+    }
+}
+
+// Whether a key of the rules is an orthography section, either "orthography" or a named one like "orthography:popular".
+pub fn is_section(key: &str) -> bool {
+    key == "orthography" || key.starts_with("orthography:")
+}
+
+// Gets every orthography of the rules tupled with its name, None for the unnamed one.
+pub fn all<'a>(rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Result<Vec<(Option<String>, Orthography)>, &'a str> {
+    let mut result = Vec::new();
+    for (key, section) in rules {
+        if is_section(key) {
+            let name = key.strip_prefix("orthography:").map(|n| n.to_owned());
+            result.push((name, Orthography::from_section(section)?));
+        }
+    }
+    Ok(result)
+}
+
+// Parses one side of a context, e.g. "#a[ei]".
+fn parse_context<'a>(context: &str) -> Result<Vec<Element>, &'a str> {
+    let mut result = Vec::new();
+    let mut chars = context.chars();
+    while let Some(c) = chars.next() {
+        let element = match c {
+            '#' => Element::Boundary,
+            '[' => {
+                let mut set = Vec::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        closed = true;
+                        break
+                    }
+                    set.push(c);
+                }
+                if set.is_empty() || !closed { return Err("Orthography context has an empty or unclosed set.") }
+                Element::Set(set)
+            }
+            c if c.is_whitespace() => continue,
+            c => Element::Char(c),
+        };
+        result.push(element);
+    }
+    Ok(result)
+}
+
+// Whether the context matches the characters right before the unit.
+fn matches_before(context: &[Element], before: &[char]) -> bool {
+    let mut position = before.len();
+    for (i, element) in context.iter().enumerate().rev() {
+        if *element == Element::Boundary {
+            // The boundary can only be the first element, at the start of the word.
+            if i != 0 || position != 0 { return false }
+            continue
+        }
+        if position == 0 || !matches_element(element, before[position - 1]) {
+            return false
+        }
+        position -= 1;
+    }
+    true
+}
+
+// Whether the context matches the characters right after the unit.
+fn matches_after(context: &[Element], after: &[char]) -> bool {
+    let mut position = 0;
+    for (i, element) in context.iter().enumerate() {
+        if *element == Element::Boundary {
+            // The boundary can only be the last element, at the end of the word.
+            if i != context.len() - 1 || position != after.len() { return false }
+            continue
+        }
+        if position == after.len() || !matches_element(element, after[position]) {
+            return false
+        }
+        position += 1;
+    }
+    true
+}
+
+// Whether a single character matches an element of a context.
+fn matches_element(element: &Element, c: char) -> bool {
+    match element {
+        Element::Char(e) => *e == c,
+        Element::Set(s) => s.contains(&c),
+        Element::Boundary => false,
+    }
+}
+
+// Picks one of the spellings by weight, given a hash. Spellings with weight 0 are never picked.
+fn pick<'a>(spellings: &[&'a Spelling], hash: u64) -> Option<&'a Spelling> {
+    let sum: u64 = spellings.iter().map(|s| s.weight as u64).sum();
+    if sum == 0 { return None }
+
+    let mut r = hash % sum;
+    for s in spellings {
+        if r < s.weight as u64 { return Some(s) }
+        r -= s.weight as u64;
+    }
+    None
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_orthography(entries: &[(&str, u32)]) -> Orthography {
+        let section = entries.iter().map(|(e, w)| (e.to_string(), *w)).collect();
+        Orthography::from_section(&section).expect("Failed to parse orthography.")
+    }
+
+    #[test]
+    fn spell_correct_values() {
+        let orthography = get_orthography(&[("tʃ > ch", 3), ("t > t", 5), ("k > c", 2), ("ʃ > sh", 2)]);
+        assert_eq!(orthography.spell("tʃat"), "chat");
+        assert_eq!(orthography.spell("tʃʃt"), "chsht");
+        assert_eq!(orthography.spell("kæt"), "cæt");
+        assert_eq!(orthography.spell(""), "");
+    }
+
+    #[test]
+    fn spell_uses_context() {
+        let orthography = get_orthography(&[("k > c", 1), ("k > qu / _[ei]", 1), ("k > k / #_", 1), ("k > ck / a_#", 1)]);
+        assert_eq!(orthography.spell("kaka"), "kaca");
+        assert_eq!(orthography.spell("akika"), "aquica");
+        assert_eq!(orthography.spell("kak"), "kack");
+        assert!(["ke", "que"].contains(&orthography.spell("ke").as_str()));
+        assert_eq!(orthography.spell("ok"), "oc");
+    }
+
+    #[test]
+    fn spell_by_weight_is_deterministic() {
+        let orthography = get_orthography(&[("a > a", 1), ("a > á", 1), ("a > à", 0)]);
+        let mut spellings = Vec::new();
+        for word in ["a", "aa", "aaa", "aaaa", "aaaaa", "aaaaaa"] {
+            let spelling = orthography.spell(word);
+            assert_eq!(spelling, orthography.spell(word));
+            assert!(!spelling.contains('à'));
+            spellings.push(spelling);
+        }
+        assert!(spellings.iter().any(|s| s.contains('a')) && spellings.iter().any(|s| s.contains('á')));
+    }
+
+    #[test]
+    fn from_section_invalid_entries() {
+        for entry in ["k: c", "k > c / e", "k > c / _[e"] {
+            let section = BTreeMap::from([(entry.to_owned(), 1)]);
+            assert!(Orthography::from_section(&section).is_err(), "{entry}");
+        }
+    }
+}
+// TESTS END
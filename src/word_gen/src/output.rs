@@ -0,0 +1,149 @@
+use crate::generator::WordRecord;
+
+// The formats generated words can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Plain,
+    JsonLines,
+    Csv,
+    Tsv,
+}
+
+impl OutputFormat {
+    // Returns the format matching a name given as an argument, e.g. "jsonl" or "csv".
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "plain" | "text" | "txt" => Some(Self::Plain),
+            "jsonl" | "json-lines" | "ndjson" => Some(Self::JsonLines),
+            "csv" => Some(Self::Csv),
+            "tsv" => Some(Self::Tsv),
+            _ => None,
+        }
+    }
+}
+
+// The fields of a record, in the order of the columns of csv and tsv.
+pub const FIELDS: [&str; 7] = ["word", "score", "length", "seed", "index", "ending", "version"];
+
+// Formats records, one line each. Plain only has the words, json lines has an object per record,
+// and csv and tsv start with a header line of the fields.
+pub fn format_records(records: &[WordRecord], format: OutputFormat) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    match format {
+        OutputFormat::Plain => lines.extend(records.iter().map(|r| r.word.clone())),
+        OutputFormat::JsonLines => {
+            for record in records {
+                let values = [
+                    json_string(&record.word),
+                    record.score.filter(|s| s.is_finite()).map_or("null".to_owned(), |s| s.to_string()),
+                    record.length.to_string(),
+                    record.seed.map_or("null".to_owned(), |s| s.to_string()),
+                    record.index.map_or("null".to_owned(), |i| i.to_string()),
+                    record.ending.as_deref().map_or("null".to_owned(), json_string),
+                    record.version.to_string(),
+                ];
+                let fields: Vec<String> = FIELDS.iter().zip(values).map(|(f, v)| format!("\"{f}\":{v}")).collect();
+                lines.push(format!("{{{}}}", fields.join(",")));
+            }
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            let (separator, escape): (&str, fn(&str) -> String) = match format {
+                OutputFormat::Csv => (",", csv_field),
+                _ => ("\t", tsv_field),
+            };
+            lines.push(FIELDS.join(separator));
+            for record in records {
+                let values = [
+                    escape(&record.word),
+                    record.score.map_or(String::new(), |s| s.to_string()),
+                    record.length.to_string(),
+                    record.seed.map_or(String::new(), |s| s.to_string()),
+                    record.index.map_or(String::new(), |i| i.to_string()),
+                    escape(record.ending.as_deref().unwrap_or("")),
+                    record.version.to_string(),
+                ];
+                lines.push(values.join(separator));
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+// Quotes a string for json, escaping quotes, backslashes and control characters.
+fn json_string(text: &str) -> String {
+    let mut result = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+// Quotes a field for csv if it has separators, quotes, line breaks or spaces at either end, doubling its quotes.
+fn csv_field(text: &str) -> String {
+    let special = text.contains([',', '"', '\n', '\r']) || text.starts_with(' ') || text.ends_with(' ');
+    if special {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
+
+// Replaces tabs and line breaks in a field for tsv, which has no way of quoting them.
+fn tsv_field(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ")
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_records() -> Vec<WordRecord> {
+        vec![
+            WordRecord { word: "kala".to_owned(), score: Some(-0.5), length: 4, seed: Some(42), index: Some(0), ending: Some("la".to_owned()), version: 1 },
+            WordRecord { word: "a\"b,c".to_owned(), score: None, length: 5, seed: None, index: None, ending: None, version: 1 },
+        ]
+    }
+
+    #[test]
+    fn from_name_correct_values() {
+        assert_eq!(OutputFormat::from_name("JSONL"), Some(OutputFormat::JsonLines));
+        assert_eq!(OutputFormat::from_name("tsv"), Some(OutputFormat::Tsv));
+        assert_eq!(OutputFormat::from_name("xml"), None);
+    }
+
+    #[test]
+    fn format_records_correct_values() {
+        let records = get_records();
+        assert_eq!(format_records(&records, OutputFormat::Plain), "kala\na\"b,c");
+        assert_eq!(
+            format_records(&records, OutputFormat::JsonLines),
+            "{\"word\":\"kala\",\"score\":-0.5,\"length\":4,\"seed\":42,\"index\":0,\"ending\":\"la\",\"version\":1}\n\
+             {\"word\":\"a\\\"b,c\",\"score\":null,\"length\":5,\"seed\":null,\"index\":null,\"ending\":null,\"version\":1}"
+        );
+        assert_eq!(
+            format_records(&records, OutputFormat::Csv),
+            "word,score,length,seed,index,ending,version\nkala,-0.5,4,42,0,la,1\n\"a\"\"b,c\",,5,,,,1"
+        );
+        assert_eq!(
+            format_records(&records, OutputFormat::Tsv),
+            "word\tscore\tlength\tseed\tindex\tending\tversion\nkala\t-0.5\t4\t42\t0\tla\t1\na\"b,c\t\t5\t\t\t\t1"
+        );
+        assert_eq!(format_records(&[], OutputFormat::Csv), "word,score,length,seed,index,ending,version");
+    }
+
+    #[test]
+    fn json_string_escapes() {
+        assert_eq!(json_string("a\\b\u{1}"), "\"a\\\\b\\u0001\"");
+        assert_eq!(json_string("ñ"), "\"ñ\"");
+    }
+}
+// TESTS END
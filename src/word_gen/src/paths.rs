@@ -0,0 +1,149 @@
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+// The environment variable with directories to search for files, separated like PATH (":" or ";" on Windows).
+pub const PATH_VARIABLE: &str = "WORD_GEN_PATH";
+
+// The name of the directory of the application in the user's config directory.
+const APPLICATION: &str = "word_gen";
+
+// Finds files given by name in a list of directories, searched in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolver {
+    pub directories: Vec<PathBuf>,
+}
+
+// A file that wasn't found, with every location that was tried, none if no file name was given.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotFound {
+    pub name: String,
+    pub tried: Vec<PathBuf>,
+}
+
+impl fmt::Display for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.name.is_empty() {
+            return write!(f, "No file was given.")
+        }
+        write!(f, "Couldn't find \"{}\". Tried:", self.name)?;
+        for path in &self.tried {
+            write!(f, "\n  {}", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+impl Resolver {
+    // Creates a resolver searching the given directories, then the ones in the environment variable, the user's config
+    // directory, and the local and example assets of the working directory.
+    pub fn from_environment(directories: &[PathBuf]) -> Self {
+        Self { directories: search_directories(directories, env::var_os(PATH_VARIABLE), config_directory()) }
+    }
+
+    // Resolves the path of a file. The path is used as it is if it exists, e.g. a full path or one relative to
+    // the working directory, otherwise the file is searched for in each directory in order.
+    pub fn resolve(&self, path: &str) -> Result<PathBuf, NotFound> {
+        if path.is_empty() {
+            return Err(NotFound { name: String::new(), tried: Vec::new() })
+        }
+
+        let given = PathBuf::from(path);
+        if given.is_file() {
+            return Ok(given)
+        }
+
+        let mut tried = vec![given.clone()];
+        if !given.is_absolute() {
+            for directory in &self.directories {
+                let candidate = directory.join(&given);
+                if candidate.is_file() {
+                    return Ok(candidate)
+                }
+                tried.push(candidate);
+            }
+        }
+        Err(NotFound { name: path.to_owned(), tried })
+    }
+}
+
+// Gets the directories to search, in order, without duplicates.
+fn search_directories(directories: &[PathBuf], variable: Option<OsString>, config: Option<PathBuf>) -> Vec<PathBuf> {
+    let mut result: Vec<PathBuf> = directories.to_vec();
+    if let Some(variable) = variable {
+        result.extend(env::split_paths(&variable).filter(|p| !p.as_os_str().is_empty()));
+    }
+    result.extend(config);
+    result.push(Path::new("assets").join("local"));
+    result.push(Path::new("assets").join("examples"));
+
+    let mut unique = Vec::new();
+    for directory in result {
+        if !unique.contains(&directory) {
+            unique.push(directory);
+        }
+    }
+    unique
+}
+
+// Gets the application's directory in the user's config directory of the platform, if it's known.
+fn config_directory() -> Option<PathBuf> {
+    let home = || env::var_os("HOME").filter(|h| !h.is_empty()).map(PathBuf::from);
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").filter(|a| !a.is_empty()).map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|h| h.join("Library").join("Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME").filter(|x| !x.is_empty()).map(PathBuf::from).or_else(|| home().map(|h| h.join(".config")))
+    };
+    base.map(|b| b.join(APPLICATION))
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    #[test]
+    fn search_directories_correct_values() {
+        let variable = env::join_paths(["first", "second"]).unwrap();
+        let result = search_directories(&[PathBuf::from("flag")], Some(variable), Some(PathBuf::from("config")));
+        let expected: Vec<PathBuf> = ["flag", "first", "second", "config"].iter().map(PathBuf::from).collect();
+        assert_eq!(result[..4], expected);
+        assert_eq!(result[4], Path::new("assets").join("local"));
+        assert_eq!(result[5], Path::new("assets").join("examples"));
+
+        // Directories given several times are only searched once.
+        let result = search_directories(&[PathBuf::from("config")], None, Some(PathBuf::from("config")));
+        assert_eq!(result.iter().filter(|d| *d == Path::new("config")).count(), 1);
+    }
+
+    #[test]
+    fn resolve_correct_values() {
+        let directory = env::temp_dir().join("word_gen_resolve_correct_values");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("rules.yaml"), "").unwrap();
+
+        let resolver = Resolver { directories: vec![PathBuf::from("missing"), directory.clone()] };
+        assert_eq!(resolver.resolve("rules.yaml"), Ok(directory.join("rules.yaml")));
+        assert_eq!(resolver.resolve("./assets/testing/test1.yaml"), Ok(PathBuf::from("./assets/testing/test1.yaml")));
+
+        // Every location tried is reported.
+        let error = resolver.resolve("other.yaml").unwrap_err();
+        assert_eq!(error.tried, [PathBuf::from("other.yaml"), Path::new("missing").join("other.yaml"), directory.join("other.yaml")]);
+        assert!(error.to_string().contains(&directory.join("other.yaml").display().to_string()));
+
+        // No file name is reported as such, without searching.
+        let error = resolver.resolve("").unwrap_err();
+        assert!(error.tried.is_empty());
+        assert_eq!(error.to_string(), "No file was given.");
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
+// TESTS END
@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+
+use crate::classes::Classes;
+use crate::rng::Pcg32;
+use crate::{reader, verification};
+
+// Consonants and vowels, from the most to the least common across languages.
+const CONSONANTS: &str = "ptkmnslrjwbdghfvzcx";
+const VOWELS: &str = "aiueoy";
+
+// How many words are synthesized to learn the patterns of a new language from.
+const LEXICON_SIZE: usize = 400;
+
+// How many times less likely the wildcard continuation of a pattern ending in a vowel is than its other continuations
+// together, letting any other letter follow the pattern now and then.
+const WILDCARD_RARITY: u32 = 50;
+
+// Typological presets, each with its own inventory sizes, syllable structure and word lengths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    // Medium inventories and syllables with occasional codas.
+    Balanced,
+    // Small inventories and open syllables only, with long words.
+    Polynesian,
+    // Large inventories with clusters in onsets and codas, and short words.
+    Germanic,
+    // Open syllables, with onsets of a nasal and a stop.
+    Bantu,
+}
+
+impl Preset {
+    // Returns the preset matching a name given as an argument, e.g. "polynesian".
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "balanced" => Some(Self::Balanced),
+            "polynesian" => Some(Self::Polynesian),
+            "germanic" => Some(Self::Germanic),
+            "bantu" => Some(Self::Bantu),
+            _ => None,
+        }
+    }
+}
+
+// The knobs of a new language: the sizes of its consonant and vowel inventories, and how complex its syllables are,
+// from 1 (consonant and vowel) to 3 (clusters in onsets and codas).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Phonology {
+    pub preset: Preset,
+    pub consonants: usize,
+    pub vowels: usize,
+    pub complexity: usize,
+}
+
+impl Phonology {
+    // Creates the knobs of a preset, which can then be changed.
+    pub fn from_preset(preset: Preset) -> Self {
+        let (consonants, vowels, complexity) = match preset {
+            Preset::Balanced => (12, 5, 2),
+            Preset::Polynesian => (8, 5, 1),
+            Preset::Germanic => (17, 6, 3),
+            Preset::Bantu => (13, 5, 1),
+        };
+        Self { preset, consonants, vowels, complexity }
+    }
+}
+
+// Synthesizes the rules of a brand-new language from the knobs. Picks the consonants and vowels, mostly common ones,
+// synthesizes words from syllables following the knobs, and learns the patterns from them, adding wildcards after vowels.
+pub fn generate_rules<'a>(rng: &mut Pcg32, phonology: &Phonology) -> Result<BTreeMap<String, BTreeMap<String, u32>>, &'a str> {
+    if !(4..=CONSONANTS.len()).contains(&phonology.consonants) {
+        return Err("Consonants must be between 4 and 19.")
+    }
+    if !(2..=VOWELS.len()).contains(&phonology.vowels) {
+        return Err("Vowels must be between 2 and 6.")
+    }
+    if !(1..=3).contains(&phonology.complexity) {
+        return Err("Complexity must be between 1 and 3.")
+    }
+
+    let consonants = pick_inventory(rng, CONSONANTS, phonology.consonants);
+    let vowels = pick_inventory(rng, VOWELS, phonology.vowels);
+    let alphabet: String = consonants.iter().chain(&vowels).map(|(c, _)| *c).collect();
+    let classes = Classes::from_alphabet(&alphabet);
+
+    // Onset clusters rise in sonority, e.g. "pl", and coda clusters fall, e.g. "nt".
+    let sonority = |c: &(char, u32)| classes.sonority(c.0);
+    let obstruents: Vec<(char, u32)> = consonants.iter().copied().filter(|c| sonority(c) <= 2).collect();
+    let liquids: Vec<(char, u32)> = consonants.iter().copied().filter(|c| sonority(c) >= 4).collect();
+    let nasals: Vec<(char, u32)> = consonants.iter().copied().filter(|c| sonority(c) == 3).collect();
+    let stops: Vec<(char, u32)> = consonants.iter().copied().filter(|c| sonority(c) == 1).collect();
+    let codas: Vec<(char, u32)> = match phonology.preset {
+        Preset::Germanic => consonants.clone(),
+        _ => consonants.iter().copied().filter(|c| (2..=4).contains(&sonority(c))).collect(),
+    };
+
+    // Percent chances of each part of a syllable.
+    let onset = if phonology.preset == Preset::Polynesian { 90 } else { 85 };
+    let (coda, cluster) = match phonology.complexity {
+        1 => (0, 0),
+        2 => (25, 0),
+        _ => (35, 25),
+    };
+    let prenasal = if phonology.preset == Preset::Bantu { 30 } else { 0 };
+    let (min_syllables, max_syllables) = match phonology.preset {
+        Preset::Polynesian | Preset::Bantu => (2, 5),
+        Preset::Germanic => (1, 3),
+        Preset::Balanced => (1, 4),
+    };
+
+    let chance = |rng: &mut Pcg32, percent: u32| rng.below(100) < percent;
+    let mut words = Vec::new();
+    for _ in 0..LEXICON_SIZE {
+        let mut word = String::new();
+        let syllables = min_syllables + rng.below((max_syllables - min_syllables + 1) as u32) as usize;
+        for _ in 0..syllables {
+            if chance(rng, prenasal) && !nasals.is_empty() && !stops.is_empty() {
+                word.push(pick(rng, &nasals));
+                word.push(pick(rng, &stops));
+            } else if chance(rng, onset) {
+                if chance(rng, cluster) && !obstruents.is_empty() && !liquids.is_empty() {
+                    word.push(pick(rng, &obstruents));
+                    word.push(pick(rng, &liquids));
+                } else {
+                    word.push(pick(rng, &consonants));
+                }
+            }
+            word.push(pick(rng, &vowels));
+            if chance(rng, coda) && !codas.is_empty() {
+                if chance(rng, cluster) && !nasals.is_empty() && !stops.is_empty() {
+                    word.push(pick(rng, &nasals));
+                    word.push(pick(rng, &stops));
+                } else {
+                    word.push(pick(rng, &codas));
+                }
+            }
+        }
+        words.push(word);
+    }
+
+    // Learn the patterns from the words, and let any letter follow a vowel now and then. The wildcard goes on every
+    // pattern ending in a vowel, since the longest matching pattern is the one used, but only if some letter can't follow it yet.
+    let mut rules = reader::rules_from_string(&words.join(" "), 3);
+    let alphabet = rules["alphabet"].first_key_value().map_or(String::new(), |(a, _)| a.to_owned());
+    for (pattern, continuations) in rules.iter_mut() {
+        let ends_in_vowel = pattern.chars().last().is_some_and(|c| vowels.iter().any(|(v, _)| *v == c));
+        let missing = alphabet.chars().any(|c| !continuations.contains_key(&c.to_string()));
+        if ends_in_vowel && missing && pattern != "alphabet" {
+            let total: u32 = continuations.values().sum();
+            continuations.values_mut().for_each(|w| *w *= WILDCARD_RARITY);
+            continuations.insert("_".to_owned(), total);
+        }
+    }
+
+    if verification::verify_rules(&rules).is_err() {
+        return Err("Synthesized rules are not valid.")
+    }
+    Ok(rules)
+}
+
+// Picks the letters of an inventory, mostly the most common ones, each weighted by how common it is among them.
+fn pick_inventory(rng: &mut Pcg32, letters: &str, size: usize) -> Vec<(char, u32)> {
+    let mut remaining: Vec<char> = letters.chars().collect();
+    let mut result = Vec::new();
+    for i in 0..size {
+        // The letter is one of the 3 most common ones remaining.
+        let index = rng.below(remaining.len().min(3) as u32) as usize;
+        result.push((remaining.remove(index), (size - i) as u32));
+    }
+    result
+}
+
+// Picks one of the letters at random by weight.
+fn pick(rng: &mut Pcg32, letters: &[(char, u32)]) -> char {
+    let sum: u32 = letters.iter().map(|(_, w)| w).sum();
+    let mut r = rng.below(sum);
+    for (letter, weight) in letters {
+        if r < *weight { return *letter }
+        r -= weight;
+    }
+    letters.last().unwrap().0
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::SeedableRng;
+
+    #[test]
+    fn pick_inventory_correct_values() {
+        let mut rng = Pcg32::seed_from_u64(0);
+        let inventory = pick_inventory(&mut rng, VOWELS, 4);
+        assert_eq!(inventory.len(), 4);
+        assert_eq!(inventory.iter().map(|(_, w)| *w).collect::<Vec<u32>>(), [4, 3, 2, 1]);
+        assert!(inventory.iter().all(|(c, _)| VOWELS.contains(*c)));
+    }
+
+    #[test]
+    fn generate_rules_invalid_knobs() {
+        let mut rng = Pcg32::seed_from_u64(0);
+        let phonology = Phonology::from_preset(Preset::Balanced);
+        assert!(generate_rules(&mut rng, &Phonology { consonants: 3, ..phonology }).is_err());
+        assert!(generate_rules(&mut rng, &Phonology { vowels: 7, ..phonology }).is_err());
+        assert!(generate_rules(&mut rng, &Phonology { complexity: 0, ..phonology }).is_err());
+    }
+}
+// TESTS END
@@ -0,0 +1,291 @@
+use std::collections::BTreeMap;
+
+use crate::reader;
+
+// Phonemes written with several characters that are still a single unit, e.g. when spelling them.
+const MULTI_CHARACTER_PHONEMES: [&str; 10] = ["tʃ", "dʒ", "ts", "dz", "aɪ", "aʊ", "ɔɪ", "eɪ", "oʊ", "əʊ"];
+
+// Characters that modify the preceding phoneme instead of being phonemes themselves.
+const MODIFIERS: &str = "ːˑʰʲʷˠˤⁿˡ";
+
+// Characters of a transcription that are not part of any phoneme: stress marks, syllable breaks, ties, etc.
+const IGNORED: &str = "ˈˌ.‿|'\u{361}\u{35c}";
+
+// The relative likelihood of a phoneme being spelled with 0, 1, 2, 3 or 4 letters before anything is learned.
+const CHUNK_LENGTH_PRIOR: [f64; 5] = [0.01, 0.6, 0.3, 0.06, 0.03];
+
+// How many counted alignments the prior is worth when aligning.
+const PRIOR_WEIGHT: f64 = 2.0;
+
+// Vowel phonemes, and the letters usually spelling them.
+const VOWELS: &str = "aeiouyæɑɒɐɔəɚɛɜɝɪɨʊʉʌøœɯɤ";
+const VOWEL_LETTERS: &str = "aeiouy";
+
+// How many times the alignments are refined using the spellings counted in the previous round.
+const ALIGNMENT_ROUNDS: usize = 4;
+
+// Creates the rules for a language based on a pronunciation dictionary.
+// The patterns are learned from the IPA transcriptions, and the orthography section maps each phoneme to its spellings.
+pub fn rules_from_dictionary(text: &str, depth: usize) -> Result<BTreeMap<String, BTreeMap<String, u32>>, &'static str> {
+    let entries = parse_dictionary(text);
+    if entries.is_empty() {
+        return Err("No entries in pronunciation dictionary.")
+    }
+
+    // Learn the patterns from the transcriptions, as if they were a sample text.
+    let transcriptions: Vec<String> = entries.iter().map(|(_, p)| p.concat()).collect();
+    let mut rules = reader::rules_from_string(&transcriptions.join(" "), depth);
+
+    // Learn the spellings of each phoneme by aligning the transcriptions with their words.
+    let mut counts: BTreeMap<String, BTreeMap<String, u32>> = BTreeMap::new();
+    for _ in 0..ALIGNMENT_ROUNDS {
+        let mut new_counts: BTreeMap<String, BTreeMap<String, u32>> = BTreeMap::new();
+        for (word, phonemes) in &entries {
+            let letters: Vec<char> = word.chars().collect();
+            for (phoneme, chunk) in align(phonemes, &letters, &counts).unwrap_or_default() {
+                *new_counts.entry(phoneme).or_default().entry(chunk).or_insert(0) += 1;
+            }
+        }
+        counts = new_counts;
+    }
+
+    // Phonemes without letters are alignment artifacts, e.g. the second phoneme of "x", and are not spellings.
+    let mut orthography = BTreeMap::new();
+    for (phoneme, chunks) in &counts {
+        for (chunk, count) in chunks {
+            if !chunk.is_empty() {
+                orthography.insert(format!("{phoneme} > {chunk}"), *count);
+            }
+        }
+    }
+    rules.insert("orthography".to_owned(), orthography);
+
+    Ok(rules)
+}
+
+// Parses the entries of a pronunciation dictionary into words and their phonemes in IPA.
+// Both CMUdict style "WORD  PH1 PH2" lines with ARPAbet phonemes and "word<TAB>/ipa/" lines are supported.
+// Comments, alternative pronunciations and words with non-alphabetic characters are skipped.
+pub fn parse_dictionary(text: &str) -> Vec<(String, Vec<String>)> {
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(";;;") || line.starts_with('#') {
+            continue
+        }
+
+        let (word, phonemes) = match line.split_once('\t') {
+            Some((word, ipa)) if ipa.contains('/') => {
+                // Only the first of several transcriptions, e.g. "/ipa1/, /ipa2/", is used.
+                let ipa = ipa.split('/').find(|p| !p.trim().is_empty() && !p.contains(',')).unwrap_or("");
+                (word.trim(), segment_ipa(ipa))
+            }
+            _ => {
+                let mut parts = line.split_whitespace();
+                let word = parts.next().unwrap_or("");
+                let phonemes: Option<Vec<String>> = parts.map(arpabet_to_ipa).collect();
+                (word, phonemes.unwrap_or_default())
+            }
+        };
+
+        if phonemes.is_empty() || !word.chars().all(|c| c.is_alphabetic()) {
+            continue
+        }
+        entries.push((word.to_lowercase(), phonemes));
+    }
+    entries
+}
+
+// Splits an IPA transcription into phonemes, keeping modifiers with their phonemes.
+pub fn segment_ipa(ipa: &str) -> Vec<String> {
+    let mut phonemes: Vec<String> = Vec::new();
+    for c in ipa.chars() {
+        if IGNORED.contains(c) || c.is_whitespace() {
+            continue
+        }
+
+        // Modifiers and combining diacritics belong to the previous phoneme.
+        let combining = reader::is_combining(c);
+        if let Some(last) = phonemes.last_mut() {
+            let joined = format!("{last}{c}");
+            if MODIFIERS.contains(c) || combining || MULTI_CHARACTER_PHONEMES.contains(&joined.as_str()) {
+                *last = joined;
+                continue
+            }
+        }
+        phonemes.push(c.to_string());
+    }
+    phonemes
+}
+
+// Converts an ARPAbet phoneme such as "AH0" into IPA. Unstressed AH and ER are reduced vowels.
+fn arpabet_to_ipa(phoneme: &str) -> Option<String> {
+    let unstressed = phoneme.ends_with('0');
+    let ipa = match phoneme.trim_end_matches(|c: char| c.is_ascii_digit()) {
+        "AA" => "ɑ",
+        "AE" => "æ",
+        "AH" => if unstressed { "ə" } else { "ʌ" },
+        "AO" => "ɔ",
+        "AW" => "aʊ",
+        "AY" => "aɪ",
+        "B" => "b",
+        "CH" => "tʃ",
+        "D" => "d",
+        "DH" => "ð",
+        "EH" => "ɛ",
+        "ER" => if unstressed { "ɚ" } else { "ɝ" },
+        "EY" => "eɪ",
+        "F" => "f",
+        "G" => "g",
+        "HH" => "h",
+        "IH" => "ɪ",
+        "IY" => "i",
+        "JH" => "dʒ",
+        "K" => "k",
+        "L" => "l",
+        "M" => "m",
+        "N" => "n",
+        "NG" => "ŋ",
+        "OW" => "oʊ",
+        "OY" => "ɔɪ",
+        "P" => "p",
+        "R" => "ɹ",
+        "S" => "s",
+        "SH" => "ʃ",
+        "T" => "t",
+        "TH" => "θ",
+        "UH" => "ʊ",
+        "UW" => "u",
+        "V" => "v",
+        "W" => "w",
+        "Y" => "j",
+        "Z" => "z",
+        "ZH" => "ʒ",
+        _ => return None,
+    };
+    Some(ipa.to_owned())
+}
+
+// Aligns the phonemes of a word with its letters, so that each phoneme is spelled by a chunk of 0 to 4 letters.
+// The alignment with the highest likelihood is chosen, based on the spellings counted so far.
+fn align(phonemes: &[String], letters: &[char], counts: &BTreeMap<String, BTreeMap<String, u32>>) -> Option<Vec<(String, String)>> {
+    let max_chunk = CHUNK_LENGTH_PRIOR.len() - 1;
+
+    // best[i][j] is the score and chunk length of the best alignment of the first i phonemes with the first j letters.
+    let mut best: Vec<Vec<Option<(f64, usize)>>> = vec![vec![None; letters.len() + 1]; phonemes.len() + 1];
+    best[0][0] = Some((0.0, 0));
+
+    for i in 1..=phonemes.len() {
+        let total: u32 = counts.get(&phonemes[i - 1]).map_or(0, |m| m.values().sum());
+        for j in 0..=letters.len() {
+            for k in 0..=max_chunk.min(j) {
+                let previous = match best[i - 1][j - k] {
+                    Some((s, _)) => s,
+                    None => continue,
+                };
+                let chunk: String = letters[j - k..j].iter().collect();
+                let count = counts.get(&phonemes[i - 1]).and_then(|m| m.get(&chunk)).copied().unwrap_or(0);
+                let prior = prior(&phonemes[i - 1], &chunk);
+                let score = previous + ((count as f64 + PRIOR_WEIGHT * prior) / (total as f64 + PRIOR_WEIGHT)).ln();
+                if best[i][j].is_none_or(|(s, _)| score > s) {
+                    best[i][j] = Some((score, k));
+                }
+            }
+        }
+    }
+
+    // Trace the best alignment back from the end of the word.
+    let mut result = Vec::new();
+    let mut j = letters.len();
+    for i in (1..=phonemes.len()).rev() {
+        let (_, k) = best[i][j]?;
+        result.push((phonemes[i - 1].to_owned(), letters[j - k..j].iter().collect()));
+        j -= k;
+    }
+    if j != 0 {
+        return None
+    }
+    result.reverse();
+    Some(result)
+}
+
+// The likelihood of a phoneme being spelled with a chunk before anything is learned.
+// Chunks starting with a letter that resembles the phoneme, and continuing with letters of the same kind (vowel or consonant), are more likely.
+fn prior(phoneme: &str, chunk: &str) -> f64 {
+    let mut result = CHUNK_LENGTH_PRIOR[chunk.chars().count()];
+    let vowel = phoneme.chars().next().is_some_and(|c| VOWELS.contains(c));
+    for (i, letter) in chunk.chars().enumerate() {
+        if i == 0 && resembles(phoneme, letter) {
+            result *= 4.0;
+        } else if i > 0 && VOWEL_LETTERS.contains(letter) == vowel {
+            result *= 2.0;
+        }
+    }
+    result
+}
+
+// Whether a letter is a common spelling of (the start of) a phoneme.
+fn resembles(phoneme: &str, letter: char) -> bool {
+    const PAIRS: [(char, &str); 9] = [('ʃ', "sc"), ('ʒ', "sgz"), ('θ', "t"), ('ð', "t"), ('ŋ', "n"), ('ɹ', "r"), ('j', "y"), ('k', "cq"), ('ɡ', "g")];
+
+    let first = match phoneme.chars().next() {
+        Some(c) => c,
+        None => return false,
+    };
+    if first == letter {
+        return true
+    }
+    if VOWELS.contains(first) {
+        return VOWEL_LETTERS.contains(letter)
+    }
+    PAIRS.iter().any(|(p, letters)| *p == first && letters.contains(letter))
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_ipa_correct_values() {
+        assert_eq!(segment_ipa("ˈtʃɛk"), ["tʃ", "ɛ", "k"]);
+        assert_eq!(segment_ipa("ˈbaɪ.sɪ.kəl"), ["b", "aɪ", "s", "ɪ", "k", "ə", "l"]);
+        assert_eq!(segment_ipa("kʰaːt"), ["kʰ", "aː", "t"]);
+    }
+
+    #[test]
+    fn parse_dictionary_correct_values() {
+        let text = ";;; comment\nCHECK  CH EH1 K\nCHECK(1)  CH EH1 K\nO'CLOCK  AH0 K L AA1 K\ncat\t/kæt/\n";
+        let entries = parse_dictionary(text);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0], ("check".to_owned(), vec!["tʃ".to_owned(), "ɛ".to_owned(), "k".to_owned()]));
+        assert_eq!(entries[1], ("cat".to_owned(), vec!["k".to_owned(), "æ".to_owned(), "t".to_owned()]));
+    }
+
+    #[test]
+    fn rules_from_dictionary_keeps_diacritics() {
+        let text = "bon\t/bɔ̃/\nvin\t/vɛ̃/\n";
+        let rules = rules_from_dictionary(text, 2).expect("Failed to create rules.");
+
+        // The nasalization stays with its vowel instead of splitting the transcription into two words.
+        assert!(rules["ɔ"].contains_key("\u{303}"));
+        assert!(!rules[" "].contains_key("\u{303}"));
+        assert_eq!(rules["word_length"]["min"], "bɔ̃".len() as u32);
+        assert!(rules["orthography"].contains_key("ɔ̃ > on"));
+    }
+
+    #[test]
+    fn align_correct_values() {
+        let counts = BTreeMap::new();
+        let phonemes: Vec<String> = ["tʃ", "ɛ", "k"].map(|s| s.to_owned()).to_vec();
+        let letters: Vec<char> = "check".chars().collect();
+        let alignment = align(&phonemes, &letters, &counts).expect("Failed to align.");
+        assert_eq!(alignment, [("tʃ", "ch"), ("ɛ", "e"), ("k", "ck")].map(|(p, c)| (p.to_owned(), c.to_owned())));
+
+        // Words with more than 4 letters per phoneme can't be aligned.
+        let phonemes = vec!["a".to_owned()];
+        let letters: Vec<char> = "aaaaa".chars().collect();
+        assert_eq!(align(&phonemes, &letters, &counts), None);
+    }
+}
+// TESTS END
@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+
+use crate::classes::Classes;
+use crate::{seed, syllable};
+
+// The mark written before a stressed syllable.
+pub const STRESS_MARK: char = 'ˈ';
+
+// The positions of the stressed syllable in a fixed stress system, counted from the start or the end of the word.
+const POSITIONS: [(&str, bool, usize); 5] = [
+    ("initial", false, 0),
+    ("second", false, 1),
+    ("final", true, 0),
+    ("penultimate", true, 1),
+    ("antepenultimate", true, 2),
+];
+
+// A stress system: the position of the stressed syllable, and whether it moves away from a light syllable.
+#[derive(Debug, Clone, PartialEq)]
+struct Stress {
+    from_end: bool,
+    position: usize,
+    weight_sensitive: bool,
+}
+
+// A tone inventory: the marks of each tone with their weights, and how likely each tone is to follow another.
+#[derive(Debug, Clone, PartialEq)]
+struct Tones {
+    tones: Vec<(String, String, u32)>,
+    sequences: BTreeMap<(String, String), u32>,
+}
+
+// The prosody of a language, i.e. its stress system and tones, used to annotate and render syllabified words.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prosody {
+    classes: Classes,
+    stress: Option<Stress>,
+    tones: Option<Tones>,
+}
+
+// A syllable of a word, annotated with whether it is stressed and its tone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Syllable {
+    pub syllable: String,
+    pub stressed: bool,
+    pub tone: Option<String>,
+}
+
+impl Prosody {
+    // Creates the prosody from the stress, tones and tone_sequences sections of the rules.
+    // The stress section has one of the positions initial, second, final, penultimate or antepenultimate,
+    // and optionally "weight-sensitive", e.g. "penultimate: 0" and "weight-sensitive: 0" for Latin-like stress.
+    // The tones section maps each tone to its mark, with weights, e.g. "H > \u{301}: 2".
+    // The tone_sequences section multiplies the weight of a tone following another one, e.g. "H H: 0" forbids two
+    // high tones in a row, and "# L: 3" makes the first tone of a word 3 times as likely to be low. Unlisted sequences have 1.
+    pub fn from_rules<'a>(rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Result<Self, &'a str> {
+        let classes = Classes::from_rules(rules)?;
+
+        let stress = match rules.get("stress") {
+            Some(section) => {
+                let mut positions = POSITIONS.iter().filter(|(name, _, _)| section.contains_key(*name));
+                let (_, from_end, position) = match (positions.next(), positions.next()) {
+                    (Some(p), None) => p,
+                    _ => return Err("Stress doesn't have exactly one position."),
+                };
+                if section.keys().any(|k| k != "weight-sensitive" && !POSITIONS.iter().any(|(name, _, _)| name == k)) {
+                    return Err("Stress has an unknown entry.")
+                }
+                Some(Stress { from_end: *from_end, position: *position, weight_sensitive: section.contains_key("weight-sensitive") })
+            }
+            None => None,
+        };
+
+        let tones = match rules.get("tones") {
+            Some(section) => {
+                let mut tones = Vec::new();
+                for (entry, weight) in section {
+                    match entry.split_once(" > ") {
+                        Some((tone, mark)) if !tone.is_empty() && !tone.contains(' ') => tones.push((tone.to_owned(), mark.to_owned(), *weight)),
+                        _ => return Err("Tone is not in the form \"tone > mark\"."),
+                    }
+                }
+                if tones.iter().all(|(_, _, w)| *w == 0) {
+                    return Err("No tones with weight in tones.")
+                }
+
+                let mut sequences = BTreeMap::new();
+                for (entry, weight) in rules.get("tone_sequences").into_iter().flatten() {
+                    let (first, second) = entry.split_once(' ').ok_or("Tone sequence is not in the form \"tone tone\".")?;
+                    let known = |t: &str| tones.iter().any(|(tone, _, _)| tone == t);
+                    if !(known(first) || first == "#") || !(known(second) || second == "#") {
+                        return Err("Tone sequence uses an undefined tone.")
+                    }
+                    sequences.insert((first.to_owned(), second.to_owned()), *weight);
+                }
+                Some(Tones { tones, sequences })
+            }
+            None => None,
+        };
+
+        Ok(Self { classes, stress, tones })
+    }
+
+    // Splits a word into syllables, annotated with stress and tones.
+    // Tones are picked by weight based on a hash of the word, so a word always gets the same tones.
+    pub fn annotate(&self, word: &str) -> Vec<Syllable> {
+        let syllables = syllable::syllabify(word, &self.classes);
+        let stressed = self.stress.as_ref().and_then(|s| self.stressed_syllable(s, &syllables));
+        let tones = match &self.tones {
+            Some(t) => assign_tones(t, word, syllables.len()),
+            None => vec![None; syllables.len()],
+        };
+
+        syllables.into_iter().zip(tones).enumerate().map(|(i, (syllable, tone))| Syllable {
+            syllable,
+            stressed: stressed == Some(i),
+            tone,
+        }).collect()
+    }
+
+    // Renders a word with a stress mark before its stressed syllable and the tone marks after the first vowel of each
+    // syllable, with the syllables joined by a separator, e.g. "ba-ˈna-na" with "-".
+    // Monosyllables are not marked for stress.
+    pub fn render(&self, word: &str, separator: &str) -> String {
+        let syllables = self.annotate(word);
+        let monosyllable = syllables.len() < 2;
+        let rendered: Vec<String> = syllables.into_iter().map(|s| {
+            let mut result = String::new();
+            if s.stressed && !monosyllable {
+                result.push(STRESS_MARK);
+            }
+
+            // Marks of tones are put after the first vowel of the syllable.
+            let mark = s.tone.as_ref().and_then(|t| self.mark(t)).unwrap_or("");
+            let mut marked = mark.is_empty();
+            for c in s.syllable.chars() {
+                result.push(c);
+                if !marked && self.classes.is_vowel(c) {
+                    result.push_str(mark);
+                    marked = true;
+                }
+            }
+            result
+        }).collect();
+        rendered.join(separator)
+    }
+
+    // Gets the mark of a tone.
+    fn mark(&self, tone: &str) -> Option<&str> {
+        self.tones.as_ref()?.tones.iter().find(|(t, _, _)| t == tone).map(|(_, m, _)| m.as_str())
+    }
+
+    // Gets the index of the stressed syllable. Weight-sensitive stress moves away from the edge of the word
+    // when the syllable in the fixed position is light, i.e. it has neither a coda nor a long vowel.
+    fn stressed_syllable(&self, stress: &Stress, syllables: &[String]) -> Option<usize> {
+        if syllables.is_empty() {
+            return None
+        }
+
+        // Words shorter than the position are stressed on the syllable closest to it.
+        let last = syllables.len() - 1;
+        let position = stress.position.min(last);
+        let mut index = if stress.from_end { last - position } else { position };
+
+        if stress.weight_sensitive && !self.is_heavy(&syllables[index]) {
+            if stress.from_end && index > 0 {
+                index -= 1;
+            } else if !stress.from_end && index < last {
+                index += 1;
+            }
+        }
+        Some(index)
+    }
+
+    // Whether a syllable is heavy, i.e. it ends with a consonant or has a long vowel.
+    fn is_heavy(&self, syllable: &str) -> bool {
+        let chars: Vec<char> = syllable.chars().collect();
+        let vowels = chars.iter().filter(|c| self.classes.is_vowel(**c)).count();
+        let coda = chars.last().is_some_and(|c| !self.classes.is_vowel(*c) && *c != 'ː');
+        coda || vowels > 1 || chars.contains(&'ː')
+    }
+}
+
+// Assigns a tone to each syllable, picking each by weight multiplied by the weight of its sequence with the previous tone,
+// and with the end of the word for the last syllable. If no tone is possible, the one with the highest weight is used.
+fn assign_tones(tones: &Tones, word: &str, syllables: usize) -> Vec<Option<String>> {
+    let mut result = Vec::new();
+    let mut previous = "#".to_owned();
+
+    for i in 0..syllables {
+        let sequence = |first: &str, second: &str| *tones.sequences.get(&(first.to_owned(), second.to_owned())).unwrap_or(&1) as u64;
+        let weights: Vec<u64> = tones.tones.iter().map(|(tone, _, weight)| {
+            let end = if i == syllables - 1 { sequence(tone, "#") } else { 1 };
+            *weight as u64 * sequence(&previous, tone) * end
+        }).collect();
+
+        let sum: u64 = weights.iter().sum();
+        let index = if sum == 0 {
+            (0..tones.tones.len()).max_by_key(|t| (tones.tones[*t].2, std::cmp::Reverse(*t))).unwrap()
+        } else {
+            // The syllable is hashed as a u64, so the word gets the same tones on every platform.
+            let mut r = seed::fnv1a(word.bytes().chain((i as u64).to_le_bytes())) % sum;
+            weights.iter().position(|w| if r < *w { true } else { r -= w; false }).unwrap()
+        };
+
+        previous = tones.tones[index].0.to_owned();
+        result.push(Some(previous.clone()));
+    }
+    result
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_prosody(stress: &[&str], tones: &[(&str, u32)], sequences: &[(&str, u32)]) -> Prosody {
+        let mut rules = BTreeMap::from([("alphabet".to_owned(), BTreeMap::from([("abeiklmnrst".to_owned(), 0)]))]);
+        if !stress.is_empty() {
+            rules.insert("stress".to_owned(), stress.iter().map(|s| (s.to_string(), 0)).collect());
+        }
+        if !tones.is_empty() {
+            rules.insert("tones".to_owned(), tones.iter().map(|(t, w)| (t.to_string(), *w)).collect());
+            rules.insert("tone_sequences".to_owned(), sequences.iter().map(|(s, w)| (s.to_string(), *w)).collect());
+        }
+        Prosody::from_rules(&rules).expect("Failed to read prosody.")
+    }
+
+    #[test]
+    fn render_fixed_stress() {
+        assert_eq!(get_prosody(&["initial"], &[], &[]).render("banana", "-"), "ˈba-na-na");
+        assert_eq!(get_prosody(&["penultimate"], &[], &[]).render("banana", ""), "baˈnana");
+        assert_eq!(get_prosody(&["antepenultimate"], &[], &[]).render("banana", "-"), "ˈba-na-na");
+        assert_eq!(get_prosody(&["antepenultimate"], &[], &[]).render("bana", "-"), "ˈba-na");
+        assert_eq!(get_prosody(&["final"], &[], &[]).render("ban", "-"), "ban");
+        assert_eq!(get_prosody(&[], &[], &[]).render("banana", "-"), "ba-na-na");
+    }
+
+    #[test]
+    fn render_weight_sensitive_stress() {
+        let prosody = get_prosody(&["penultimate", "weight-sensitive"], &[], &[]);
+        assert_eq!(prosody.render("banana", "-"), "ˈba-na-na");
+        assert_eq!(prosody.render("banantra", "-"), "ba-ˈnan-tra");
+        assert_eq!(prosody.render("banaana", "-"), "ba-ˈnaa-na");
+    }
+
+    #[test]
+    fn render_tones() {
+        let prosody = get_prosody(&[], &[("H > \u{301}", 1), ("L > \u{300}", 1)], &[("H H", 0), ("L L", 0), ("# L", 0)]);
+        assert_eq!(prosody.render("banana", "-"), "ba\u{301}-na\u{300}-na\u{301}");
+        assert_eq!(prosody.render("stra", ""), "stra\u{301}");
+
+        let syllables = prosody.annotate("kilimanjaro");
+        let tones: Vec<&str> = syllables.iter().map(|s| s.tone.as_deref().unwrap()).collect();
+        assert_eq!(tones, ["H", "L", "H", "L", "H"]);
+    }
+
+    #[test]
+    fn from_rules_invalid_prosody() {
+        let rules = |section: &str, entries: &[&str]| BTreeMap::from([
+            ("alphabet".to_owned(), BTreeMap::from([("ab".to_owned(), 0)])),
+            ("tones".to_owned(), BTreeMap::from([("H > \u{301}".to_owned(), 1)])),
+            (section.to_owned(), entries.iter().map(|e| (e.to_string(), 0)).collect()),
+        ]);
+        assert!(Prosody::from_rules(&rules("stress", &["initial", "final"])).is_err());
+        assert!(Prosody::from_rules(&rules("stress", &["initial", "heavy"])).is_err());
+        assert!(Prosody::from_rules(&rules("stress", &[])).is_err());
+        assert!(Prosody::from_rules(&rules("tone_sequences", &["H L"])).is_err());
+        assert!(Prosody::from_rules(&rules("tone_sequences", &["HH"])).is_err());
+        assert!(Prosody::from_rules(&rules("tone_sequences", &["# H"])).is_ok());
+    }
+}
+// TESTS END
@@ -0,0 +1,92 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::rng::{ALGORITHM, ALGORITHM_VERSION};
+use crate::seed;
+
+// Where learned rules came from: the name of the file they were learned from, the hash of its contents,
+// the depth of the learned patterns and the date they were learned on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    pub source: String,
+    pub hash: u64,
+    pub depth: usize,
+    pub date: String,
+}
+
+impl Provenance {
+    // Creates the provenance of rules learned today from the contents of a file.
+    pub fn new(source: &str, contents: &[u8], depth: usize) -> Self {
+        Self { source: source.to_owned(), hash: seed::fnv1a(contents.iter().copied()), depth, date: date(SystemTime::now()) }
+    }
+
+    // Adds the provenance to the yaml of the rules as comments, so the rules can still be read as they are.
+    pub fn annotate(&self, yaml: &str) -> String {
+        format!(
+            "# Learned by word_gen from \"{}\".\n# source: {}\n# hash: fnv1a64:{:016x}\n# depth: {}\n# date: {}\n{}{yaml}",
+            self.source, self.source, self.hash, self.depth, self.date, rng_line(),
+        )
+    }
+}
+
+// Adds how new rules were made, e.g. "invented", and the seed they were generated from if any, to their yaml as comments,
+// along with the version of the rng, since the same seed only generates the same rules with the same version.
+pub fn annotate_new(yaml: &str, origin: &str, seed: Option<u64>) -> String {
+    let seed = seed.map_or(String::new(), |s| format!("# seed: {s}\n"));
+    format!("# {origin} by word_gen.\n{seed}{}{yaml}", rng_line())
+}
+
+// The comment line with the algorithm and version of the rng.
+fn rng_line() -> String {
+    format!("# rng: {ALGORITHM} version {ALGORITHM_VERSION}\n")
+}
+
+// Formats the UTC date of a time as "YYYY-MM-DD", converting the days since the epoch to the civil calendar.
+fn date(time: SystemTime) -> String {
+    let days = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() / 86400) as i64;
+
+    // Count from 0000-03-01, so leap days are at the end of each year, in eras of 400 years.
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    #[test]
+    fn date_correct_values() {
+        let day = |days: u64| date(UNIX_EPOCH + Duration::from_secs(days * 86400 + 3600));
+        assert_eq!(day(0), "1970-01-01");
+        assert_eq!(day(19782), "2024-02-29");
+        assert_eq!(day(19783), "2024-03-01");
+        assert_eq!(day(11016), "2000-02-29");
+        assert_eq!(day(10956), "1999-12-31");
+    }
+
+    #[test]
+    fn annotate_correct_values() {
+        let provenance = Provenance { source: "sample.txt".to_owned(), hash: 255, depth: 3, date: "2024-01-01".to_owned() };
+        let yaml = provenance.annotate("alphabet:\n  ab: 0\n");
+        assert!(yaml.starts_with("# Learned by word_gen from \"sample.txt\".\n"));
+        assert!(yaml.contains("# hash: fnv1a64:00000000000000ff\n# depth: 3\n# date: 2024-01-01\n# rng: pcg32 version 1\n"));
+        assert!(yaml.ends_with("alphabet:\n  ab: 0\n"));
+    }
+
+    #[test]
+    fn annotate_new_correct_values() {
+        assert_eq!(annotate_new("a: {}\n", "Invented", Some(42)), "# Invented by word_gen.\n# seed: 42\n# rng: pcg32 version 1\na: {}\n");
+        assert_eq!(annotate_new("a: {}\n", "Blended", None), "# Blended by word_gen.\n# rng: pcg32 version 1\na: {}\n");
+    }
+}
+// TESTS END
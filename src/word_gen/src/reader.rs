@@ -1,4 +1,7 @@
-use std::collections::{btree_map::Entry::Vacant, BTreeMap};
+use std::collections::{btree_map::Entry::Vacant, BTreeMap, BTreeSet};
+
+use crate::classes::Classes;
+use crate::harmony::{self, Tendency};
 
 // Creates the rules for a language based on a String.
 pub fn rules_from_string(text: &str, depth: usize) -> BTreeMap<String, BTreeMap<String, u32>> {
@@ -58,6 +61,24 @@ pub fn rules_from_string(text: &str, depth: usize) -> BTreeMap<String, BTreeMap<
     result
 }
 
+// Detects harmony tendencies in a String, i.e. vowels or consonants that rarely appear in the same word.
+// Returns how often each pair of vowels and of consonants appears in the same word compared to chance,
+// tupled with the harmony systems suggested by the pairs that avoid each other.
+pub fn harmony_from_string(text: &str) -> (Vec<Tendency>, Vec<String>) {
+    let text = filter_string(text);
+    let classes = Classes::from_alphabet(&get_alphabet(&text));
+    let letters = |class: char| -> Vec<char> {
+        classes.get(class).into_iter().flatten().filter_map(|m| m.chars().next()).collect()
+    };
+
+    // Every distinct word is counted once, so frequent words don't hide the tendencies.
+    let words: BTreeSet<&str> = text.split_whitespace().collect();
+    let words: Vec<&str> = words.into_iter().collect();
+    let tendencies = harmony::tendencies(&words, &[letters('V'), letters('C')]);
+    let systems = harmony::systems_from_tendencies(&tendencies);
+    (tendencies, systems)
+}
+
 // Borrowed from https://stackoverflow.com/questions/51257304/creating-a-sliding-window-iterator-of-slices-of-chars-from-a-string
 // with some modifications by clippy.
 fn char_windows(src: &str, win_size: usize) -> impl Iterator<Item = &str> {
@@ -71,9 +92,15 @@ fn char_windows(src: &str, win_size: usize) -> impl Iterator<Item = &str> {
     })
 }
 
-// Filters out all characters that are not alphabetic
-fn filter_string(text: &str) -> String {
-    let text: String = text.to_lowercase().chars().map(|c| if !c.is_alphabetic() { ' ' } else { c }).collect();
+// Filters out all characters that are not alphabetic, except combining marks following a letter, e.g. the nasalization
+// of "ɔ̃", which are part of the letter instead of separating words.
+pub(crate) fn filter_string(text: &str) -> String {
+    let mut previous = ' ';
+    let text: String = text.to_lowercase().chars().map(|c| {
+        let kept = c.is_alphabetic() || (is_combining(c) && previous != ' ');
+        previous = if kept { c } else { ' ' };
+        previous
+    }).collect();
     let mut new_text = String::from("");
 
     // Replace whitespaces (/r, /n) with spaces (' ').
@@ -89,6 +116,11 @@ fn filter_string(text: &str) -> String {
     words.join(" ")
 }
 
+// Whether a character is a combining diacritic, which modifies the character before it.
+pub(crate) fn is_combining(c: char) -> bool {
+    ('\u{300}'..='\u{36f}').contains(&c)
+}
+
 // Calculate the min, avg and max word lengths of all words in text.
 fn get_word_lengths(text: &str) -> (u32, u32, u32) {
     let mut words: Vec<u32> = text.split_whitespace().map(|word| word.len() as u32).collect();
@@ -154,6 +186,7 @@ mod tests {
     fn filter_string_correct_values() {
         assert_eq!(filter_string("123?a#,!"), "a");
         assert_eq!(filter_string(" multiple  \n  lines  \r  and  \n\n   return "), "multiple lines and return");
+        assert_eq!(filter_string("bɔ\u{303} \u{303}a"), "bɔ\u{303} a");
     }
 
     #[test]
@@ -0,0 +1,135 @@
+use rand::{Error, RngCore, SeedableRng};
+
+// The name and version of the algorithm of the rng. The words generated for a seed only stay the same as long as
+// the version does, so it's bumped whenever the algorithm or how the generator draws numbers from it changes.
+pub const ALGORITHM: &str = "pcg32";
+pub const ALGORITHM_VERSION: u32 = 1;
+
+// The multiplier of the linear congruential generator, and the stream used when seeding from a number.
+const MULTIPLIER: u64 = 6364136223846793005;
+const DEFAULT_STREAM: u64 = 54;
+
+// The rng of the crate, PCG32 (PCG-XSH-RR with 64 bits of state and 32 bit outputs), implemented here so that
+// the words generated for a seed never change with the version of rand, unlike its StdRng.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+impl Pcg32 {
+    // Creates the rng from an initial state and a stream, like pcg32_srandom of the reference implementation.
+    pub fn new(state: u64, stream: u64) -> Self {
+        let mut result = Self { state: 0, increment: (stream << 1) | 1 };
+        result.step();
+        result.state = result.state.wrapping_add(state);
+        result.step();
+        result
+    }
+
+    // Gets a number from 0 to bound (exclusive), without the bias of taking the remainder of a random number.
+    pub fn below(&mut self, bound: u32) -> u32 {
+        assert!(bound > 0);
+
+        // Reject the numbers in the incomplete range at the top, so every remainder is as likely.
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let r = self.next_u32();
+            if r >= threshold {
+                return r % bound
+            }
+        }
+    }
+
+    fn step(&mut self) {
+        self.state = self.state.wrapping_mul(MULTIPLIER).wrapping_add(self.increment);
+    }
+}
+
+impl RngCore for Pcg32 {
+    fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.step();
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rotation = (old >> 59) as u32;
+        xorshifted.rotate_right(rotation)
+    }
+
+    // Two outputs, the first one in the low bits.
+    fn next_u64(&mut self) -> u64 {
+        let low = self.next_u32() as u64;
+        let high = self.next_u32() as u64;
+        (high << 32) | low
+    }
+
+    // Fills with the little-endian bytes of outputs, dropping the rest of the last one.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for Pcg32 {
+    // The little-endian initial state followed by the stream.
+    type Seed = [u8; 16];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let state = u64::from_le_bytes(seed[..8].try_into().unwrap());
+        let stream = u64::from_le_bytes(seed[8..].try_into().unwrap());
+        Self::new(state, stream)
+    }
+
+    // Seeds the rng with the number as its initial state, instead of the default of rand, which may change.
+    fn seed_from_u64(state: u64) -> Self {
+        Self::new(state, DEFAULT_STREAM)
+    }
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_u32_matches_reference() {
+        // The first outputs of the demo of the reference implementation, seeded with 42 and stream 54.
+        let mut rng = Pcg32::new(42, 54);
+        let outputs: Vec<u32> = (0..6).map(|_| rng.next_u32()).collect();
+        assert_eq!(outputs, [0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]);
+        assert_eq!(Pcg32::seed_from_u64(42), Pcg32::new(42, 54));
+    }
+
+    #[test]
+    fn from_seed_correct_values() {
+        let mut seed = [0; 16];
+        seed[0] = 42;
+        seed[8] = 54;
+        assert_eq!(Pcg32::from_seed(seed), Pcg32::new(42, 54));
+    }
+
+    #[test]
+    fn below_returns_all_possibilities() {
+        let mut rng = Pcg32::seed_from_u64(0);
+        let mut counts = [0; 3];
+        for _ in 0..300 {
+            counts[rng.below(3) as usize] += 1;
+        }
+        assert!(counts.iter().all(|c| *c > 50));
+        assert_eq!(Pcg32::seed_from_u64(0).below(1), 0);
+    }
+
+    #[test]
+    fn fill_bytes_correct_values() {
+        let mut bytes = [0; 6];
+        Pcg32::new(42, 54).fill_bytes(&mut bytes);
+        assert_eq!(bytes, [0xb7, 0x02, 0x5c, 0xa1, 0x09, 0xf4]);
+    }
+}
+// TESTS END
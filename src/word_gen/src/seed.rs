@@ -0,0 +1,179 @@
+use rand::SeedableRng;
+
+use crate::rng::Pcg32;
+
+// The separator of the labels in a path of the seed tree, e.g. "world/north/town-3".
+pub const PATH_SEPARATOR: char = '/';
+
+// A node of a tree of seeds, where the seed of each child is derived from the seed of its parent and its label,
+// e.g. the seeds of the regions of a world and of the towns in each region, all regenerated from the root seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeedNode {
+    pub seed: u64,
+}
+
+impl SeedNode {
+    // Creates the root of a tree of seeds.
+    pub fn root(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    // Gets the child with a label, which is the same whenever it's derived, whatever other children are.
+    pub fn child(&self, label: &str) -> Self {
+        Self { seed: mix(self.seed ^ seed_from_phrase(label)) }
+    }
+
+    // Gets the descendant at a path of labels separated by "/", e.g. "world/north/town-3".
+    pub fn descend<'a>(&self, path: &str) -> Result<Self, &'a str> {
+        let mut result = *self;
+        for label in path.split(PATH_SEPARATOR) {
+            if label.is_empty() {
+                return Err("Seed paths can't have empty labels.")
+            }
+            result = result.child(label);
+        }
+        Ok(result)
+    }
+
+    // Creates the rng of the node, e.g. to generate the rules or the words of a region.
+    pub fn rng(&self) -> Pcg32 {
+        Pcg32::seed_from_u64(self.seed)
+    }
+}
+
+// Arguments that set the seed, which are replaced by the seed used in replay commands.
+const SEED_ARGUMENTS: [&str; 2] = ["--seed", "--seed-phrase"];
+
+// Arguments that set the file to save the output in, which the first run creates, so replay commands overwrite it.
+const OUTPUT_ARGUMENTS: [&str; 2] = ["-o", "--output"];
+
+// Turns a passphrase, e.g. "middle earth", into a seed, the same on every machine.
+pub fn seed_from_phrase(phrase: &str) -> u64 {
+    // FNV-1a, followed by the finalizer of SplitMix64 so similar phrases give very different seeds.
+    mix(fnv1a(phrase.bytes()))
+}
+
+// A stable FNV-1a hash of bytes, the same on every machine and with every version of the application.
+pub fn fnv1a<I: IntoIterator<Item = u8>>(bytes: I) -> u64 {
+    let mut result: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        result ^= byte as u64;
+        result = result.wrapping_mul(0x100000001b3);
+    }
+    result
+}
+
+// Derives the seed of the word at an index from the seed of all words, so any word can be generated directly
+// without generating the ones before it. Neighboring indexes and seeds give unrelated seeds.
+pub fn index_seed(seed: u64, index: u64) -> u64 {
+    mix(seed ^ mix(index.wrapping_add(0x9e3779b97f4a7c15)))
+}
+
+// The finalizer of SplitMix64, which spreads every bit of the number over all bits of the result.
+fn mix(number: u64) -> u64 {
+    let mut result = (number ^ (number >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    result = (result ^ (result >> 27)).wrapping_mul(0x94d049bb133111eb);
+    result ^ (result >> 31)
+}
+
+// Creates a command line that runs the program again with the same arguments and the seed used, so it generates
+// the same words. Any arguments setting the seed are replaced, "--force" is added if the output is saved in a file,
+// since the file exists after the first run, and arguments are quoted for the shell when needed.
+// args: the arguments the program was run with, starting with the program itself.
+pub fn replay_command(args: &[String], seed: u64) -> String {
+    let mut result: Vec<String> = Vec::new();
+    let mut skip = false;
+    let mut output = false;
+    for (i, arg) in args.iter().enumerate() {
+        if skip {
+            skip = false;
+            continue
+        }
+        let file = match arg.as_str() {
+            a if OUTPUT_ARGUMENTS.contains(&a) => args.get(i + 1).map(|f| f.as_str()),
+            a => a.strip_prefix("--output=").or(a.strip_prefix("-o").filter(|f| !f.is_empty() && !f.starts_with('-'))),
+        };
+        output |= file.is_some_and(|f| f != "-");
+        if SEED_ARGUMENTS.contains(&arg.as_str()) {
+            skip = true;
+            continue
+        }
+        if SEED_ARGUMENTS.iter().any(|s| arg.starts_with(&format!("{s}="))) {
+            continue
+        }
+        result.push(quote(arg));
+    }
+    if output && !args.iter().any(|a| a == "--force") {
+        result.push("--force".to_owned());
+    }
+    result.push(format!("--seed {seed}"));
+    result.join(" ")
+}
+
+// Quotes an argument for the shell, unless it only has characters the shell doesn't treat specially.
+fn quote(arg: &str) -> String {
+    let plain = |c: char| c.is_alphanumeric() || "-_./:=@%+,".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        arg.to_owned()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn fnv1a_correct_values() {
+        assert_eq!(fnv1a(*b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(*b"a"), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn seed_from_phrase_correct_values() {
+        assert_eq!(seed_from_phrase("middle earth"), seed_from_phrase("middle earth"));
+        assert_ne!(seed_from_phrase("middle earth"), seed_from_phrase("middle earti"));
+        assert_ne!(seed_from_phrase(""), 0);
+    }
+
+    #[test]
+    fn index_seed_correct_values() {
+        assert_eq!(index_seed(42, 5000), index_seed(42, 5000));
+        assert_ne!(index_seed(42, 0), index_seed(42, 1));
+        assert_ne!(index_seed(42, 1), index_seed(43, 1));
+        assert_ne!(index_seed(0, 0), 0);
+    }
+
+    #[test]
+    fn descend_correct_values() {
+        let root = SeedNode::root(42);
+        let town = root.descend("world/north/town-3").expect("Failed to descend.");
+        assert_eq!(town, root.child("world").child("north").child("town-3"));
+        assert_ne!(town, root.descend("world/north/town-4").unwrap());
+        assert_ne!(root.descend("a/b").unwrap(), root.descend("b/a").unwrap());
+        assert_ne!(town, SeedNode::root(43).descend("world/north/town-3").unwrap());
+        assert!(root.descend("world//town-3").is_err());
+        assert!(root.descend("").is_err());
+    }
+
+    #[test]
+    fn replay_command_correct_values() {
+        assert_eq!(replay_command(&args(&["word_gen", "-r", "example.yaml"]), 42), "word_gen -r example.yaml --seed 42");
+        assert_eq!(replay_command(&args(&["word_gen", "--seed", "1", "-r", "a.yaml"]), 1), "word_gen -r a.yaml --seed 1");
+        assert_eq!(replay_command(&args(&["word_gen", "--seed-phrase=middle earth", "-n", "5"]), 7), "word_gen -n 5 --seed 7");
+        assert_eq!(replay_command(&args(&["word_gen", "-r", "my rules.yaml", "-s", "it's"]), 3), "word_gen -r 'my rules.yaml' -s 'it'\\''s' --seed 3");
+
+        // The file the output is saved in is overwritten, unless the output is printed out.
+        assert_eq!(replay_command(&args(&["word_gen", "invent", "-o", "new.yaml"]), 2), "word_gen invent -o new.yaml --force --seed 2");
+        assert_eq!(replay_command(&args(&["word_gen", "blend", "a.yaml", "b.yaml", "--output=c.yaml"]), 2), "word_gen blend a.yaml b.yaml --output=c.yaml --force --seed 2");
+        assert_eq!(replay_command(&args(&["word_gen", "invent", "-onew.yaml", "--force"]), 2), "word_gen invent -onew.yaml --force --seed 2");
+        assert_eq!(replay_command(&args(&["word_gen", "invent", "-o", "-"]), 2), "word_gen invent -o - --seed 2");
+    }
+}
+// TESTS END
@@ -0,0 +1,230 @@
+use crate::classes::Classes;
+
+// A part of a sound change, matching a single letter, any member of a class or set, or the word boundary.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Letters(Vec<String>),
+    Boundary,
+}
+
+// What the target of a sound change is replaced with: either the same letters every time,
+// or the member of a class or set corresponding to the member of the target, e.g. "[ptk] > [bdg]".
+#[derive(Debug, Clone, PartialEq)]
+enum Replacement {
+    Letters(String),
+    Corresponding(Vec<String>),
+}
+
+// A sound change, e.g. "p > f / V_V", replacing the target with the replacement where it's in the context.
+// An empty target inserts the replacement, e.g. "> e / #_s", and an empty replacement deletes the target, e.g. "e > / _#".
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundChange {
+    target: Vec<Token>,
+    replacement: Replacement,
+    before: Vec<Token>,
+    after: Vec<Token>,
+}
+
+impl SoundChange {
+    // Parses a sound change written as "target > replacement" or "target > replacement / before_after".
+    // Targets and contexts can contain letters, classes such as V, sets of letters like [ei] and the word boundary #.
+    pub fn parse<'a>(change: &str, classes: &Classes) -> Result<Self, &'a str> {
+        let (change, context) = match change.split_once('/') {
+            Some((c, context)) => (c, Some(context.trim())),
+            None => (change, None),
+        };
+        let (target, replacement) = change.split_once('>').ok_or("Sound change is not in the form \"target > replacement\".")?;
+        let target = tokenize(target.trim(), classes)?;
+        if target.contains(&Token::Boundary) {
+            return Err("Sound change target can't contain a word boundary.")
+        }
+
+        // A class or set can be replaced with the corresponding member of another of the same size.
+        let replacement = replacement.trim().trim_start_matches('∅');
+        let replacement = match (target.as_slice(), tokenize(replacement, classes)?.as_slice()) {
+            ([Token::Letters(from)], [Token::Letters(to)]) if from.len() > 1 && to.len() > 1 => {
+                if from.len() != to.len() {
+                    return Err("Sound change replaces a class with one of a different size.")
+                }
+                Replacement::Corresponding(to.to_owned())
+            }
+            (_, tokens) if tokens.iter().all(|t| matches!(t, Token::Letters(l) if l.len() == 1)) => {
+                Replacement::Letters(replacement.to_owned())
+            }
+            _ => return Err("Sound change replacement uses a class without a matching target."),
+        };
+
+        let (before, after) = match context {
+            Some(context) => {
+                let (before, after) = context.split_once('_').ok_or("Sound change context doesn't have a _.")?;
+                (tokenize(before, classes)?, tokenize(after, classes)?)
+            }
+            None => (vec![], vec![]),
+        };
+        if before[1.min(before.len())..].contains(&Token::Boundary) || after[..after.len().saturating_sub(1)].contains(&Token::Boundary) {
+            return Err("Word boundary is not at the edge of a sound change context.")
+        }
+
+        Ok(Self { target, replacement, before, after })
+    }
+
+    // Applies the sound change to a word. Every match is found in the original word, so replacements don't
+    // create new matches, e.g. "a > b / a_" changes "aaa" to "abb".
+    pub fn apply(&self, word: &str) -> String {
+        let chars: Vec<char> = word.chars().collect();
+        let mut result = String::new();
+        let mut i = 0;
+
+        while i <= chars.len() {
+            let matched = match_forward(&self.target, &chars, i).filter(|(end, _)| {
+                match_backward(&self.before, &chars, i) && match_forward(&self.after, &chars, *end).is_some()
+            });
+
+            match matched {
+                Some((end, member)) => {
+                    match &self.replacement {
+                        Replacement::Letters(l) => result.push_str(l),
+                        Replacement::Corresponding(members) => result.push_str(&members[member.unwrap_or(0)]),
+                    }
+                    // Insertions keep the letter they were inserted before.
+                    if end == i {
+                        result.extend(chars.get(i));
+                        i += 1;
+                    } else {
+                        i = end;
+                    }
+                }
+                None => {
+                    result.extend(chars.get(i));
+                    i += 1;
+                }
+            }
+        }
+        result
+    }
+}
+
+// Parses the sound changes of a file, one per line, in the order they are applied.
+// Empty lines and lines starting with "//" are skipped.
+pub fn parse_changes<'a>(text: &str, classes: &Classes) -> Result<Vec<SoundChange>, &'a str> {
+    text.lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with("//"))
+        .map(|l| SoundChange::parse(l, classes))
+        .collect()
+}
+
+// Applies the sound changes in order to each word, returning each word tupled with its changed form.
+pub fn apply_changes(words: &[&str], changes: &[SoundChange]) -> Vec<(String, String)> {
+    words.iter().map(|w| {
+        let changed = changes.iter().fold(w.to_string(), |word, change| change.apply(&word));
+        (w.to_string(), changed)
+    }).collect()
+}
+
+// Splits a part of a sound change into tokens: letters, classes, sets of letters and word boundaries.
+fn tokenize<'a>(text: &str, classes: &Classes) -> Result<Vec<Token>, &'a str> {
+    let mut result = Vec::new();
+    let mut chars = text.chars().filter(|c| !c.is_whitespace());
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '#' => Token::Boundary,
+            '[' => {
+                let members: Vec<String> = chars.by_ref().take_while(|c| *c != ']').map(|c| c.to_string()).collect();
+                if members.is_empty() {
+                    return Err("Sound change has an empty set.")
+                }
+                Token::Letters(members)
+            }
+            '_' | '>' | '/' | ']' => return Err("Sound change has a misplaced symbol."),
+            c => match classes.get(c) {
+                Some(members) if c.is_uppercase() => Token::Letters(members.to_owned()),
+                _ => Token::Letters(vec![c.to_string()]),
+            },
+        };
+        result.push(token);
+    }
+    Ok(result)
+}
+
+// Matches the tokens starting at position i of the word, returning where the match ends, tupled with the index
+// of the member matched by the first token, if it has several. The longest members are tried first.
+fn match_forward(tokens: &[Token], chars: &[char], i: usize) -> Option<(usize, Option<usize>)> {
+    let Some((first, rest)) = tokens.split_first() else { return Some((i, None)) };
+    match first {
+        Token::Boundary => if i == chars.len() { match_forward(rest, chars, i) } else { None },
+        Token::Letters(members) => {
+            let mut order: Vec<usize> = (0..members.len()).collect();
+            order.sort_by_key(|m| std::cmp::Reverse(members[*m].chars().count()));
+            order.into_iter().find_map(|m| {
+                let end = starts_with(chars, i, &members[m])?;
+                match_forward(rest, chars, end).map(|(e, _)| (e, (members.len() > 1).then_some(m)))
+            })
+        }
+    }
+}
+
+// Matches the tokens ending at position i of the word.
+fn match_backward(tokens: &[Token], chars: &[char], i: usize) -> bool {
+    let Some((last, rest)) = tokens.split_last() else { return true };
+    match last {
+        Token::Boundary => i == 0 && match_backward(rest, chars, i),
+        Token::Letters(members) => members.iter().any(|m| {
+            let length = m.chars().count();
+            i >= length && starts_with(chars, i - length, m).is_some() && match_backward(rest, chars, i - length)
+        }),
+    }
+}
+
+// Returns where a member ends if the word has it at position i.
+fn starts_with(chars: &[char], i: usize, member: &str) -> Option<usize> {
+    let mut end = i;
+    for c in member.chars() {
+        if chars.get(end) != Some(&c) {
+            return None
+        }
+        end += 1;
+    }
+    Some(end)
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(rule: &str, word: &str) -> String {
+        let classes = Classes::from_alphabet("abdefgiklmnoprstuvz");
+        SoundChange::parse(rule, &classes).expect("Failed to parse sound change.").apply(word)
+    }
+
+    #[test]
+    fn apply_correct_values() {
+        assert_eq!(change("p > f / V_V", "papa"), "pafa");
+        assert_eq!(change("k > tʃ / _i", "kiki"), "tʃitʃi");
+        assert_eq!(change("e > / _#", "bele"), "bel");
+        assert_eq!(change("e > ∅ / _#", "bele"), "bel");
+        assert_eq!(change("s > h / #_", "sasas"), "hasas");
+        assert_eq!(change("> e / #_s", "stella"), "estella");
+        assert_eq!(change("[ptk] > [bdg] / V_V", "atakupa"), "adaguba");
+        assert_eq!(change("a > b / a_", "aaa"), "abb");
+        assert_eq!(change("nt > nd", "banti"), "bandi");
+        assert_eq!(change("V > / _V", "aorta"), "orta");
+    }
+
+    #[test]
+    fn parse_invalid_changes() {
+        let classes = Classes::from_alphabet("abdefgiklmnoprstuvz");
+        for rule in ["p f", "p > f / VV", "# > f", "[ptk] > [bd]", "p > V", "p > f / V#_", "p > f / _#V", "[] > a"] {
+            assert!(SoundChange::parse(rule, &classes).is_err(), "{rule}");
+        }
+    }
+
+    #[test]
+    fn apply_changes_in_order() {
+        let classes = Classes::from_alphabet("abdefgiklmnoprstuvz");
+        let changes = parse_changes("// Lenition.\np > f / V_V\n\nf > h / V_V\n", &classes).expect("Failed to parse sound changes.");
+        assert_eq!(apply_changes(&["apa", "pa"], &changes), [("apa".to_owned(), "aha".to_owned()), ("pa".to_owned(), "pa".to_owned())]);
+    }
+}
+// TESTS END
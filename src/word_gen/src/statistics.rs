@@ -0,0 +1,121 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::classes::Classes;
+use crate::rng::Pcg32;
+use crate::{generator, syllable, verification};
+
+// Statistics of rules, and of a sample of words generated with them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statistics {
+    pub letters: usize,
+    // The number of patterns of each length, in letters.
+    pub patterns: BTreeMap<usize, usize>,
+    pub continuations: usize,
+    // The number of continuations with weight 0.
+    pub forbidden: usize,
+    pub wildcards: usize,
+    // The number of patterns that can end a word.
+    pub terminating: usize,
+    pub word_length: (u32, u32, u32),
+    pub sample: Sample,
+}
+
+// Statistics of a sample of generated words.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub words: usize,
+    pub distinct: usize,
+    pub average_length: f32,
+    pub average_syllables: f32,
+}
+
+impl fmt::Display for Statistics {
+    // Reports the statistics line by line.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let patterns: usize = self.patterns.values().sum();
+        writeln!(f, "Letters: {}", self.letters)?;
+        writeln!(f, "Patterns: {patterns}")?;
+        for (length, count) in &self.patterns {
+            writeln!(f, "  {length} letters: {count}")?;
+        }
+        writeln!(f, "Continuations: {} ({:.2} per pattern)", self.continuations, self.continuations as f32 / patterns.max(1) as f32)?;
+        writeln!(f, "Forbidden continuations: {}", self.forbidden)?;
+        writeln!(f, "Wildcards: {}", self.wildcards)?;
+        writeln!(f, "Patterns that can end a word: {}", self.terminating)?;
+        writeln!(f, "Word length: min {}, avg {}, max {}", self.word_length.0, self.word_length.1, self.word_length.2)?;
+        write!(f, "Sample of {} words: {} distinct, {:.2} letters and {:.2} syllables on average",
+            self.sample.words, self.sample.distinct, self.sample.average_length, self.sample.average_syllables)
+    }
+}
+
+// Gets the statistics of rules, generating amount number of words with them for the statistics of the sample.
+pub fn rules_statistics<'a>(rng: &mut Pcg32, amount: u32, rules: &'a BTreeMap<String, BTreeMap<String, u32>>) -> Result<Statistics, &'a str> {
+    verification::verify_rules(rules)?;
+
+    let mut patterns = BTreeMap::new();
+    let (mut continuations, mut forbidden, mut wildcards, mut terminating) = (0, 0, 0, 0);
+    for (pattern, map) in rules.iter().filter(|(k, _)| !verification::is_section(k)) {
+        *patterns.entry(pattern.chars().count()).or_default() += 1;
+        continuations += map.len();
+        forbidden += map.values().filter(|w| **w == 0).count();
+        wildcards += map.keys().filter(|c| c.contains('_')).count();
+        if map.get(" ").is_some_and(|w| *w > 0) {
+            terminating += 1;
+        }
+    }
+
+    // Generate the sample, counting its syllables with the classes of the rules.
+    let classes = Classes::from_rules(rules)?;
+    let words = if amount == 0 { String::new() } else { generator::generate_words(rng, amount, rules)? };
+    let words: Vec<&str> = words.split_whitespace().collect();
+    let average = |sum: usize| if words.is_empty() { 0.0 } else { sum as f32 / words.len() as f32 };
+    let sample = Sample {
+        words: words.len(),
+        distinct: words.iter().collect::<BTreeSet<_>>().len(),
+        average_length: average(words.iter().map(|w| w.chars().count()).sum()),
+        average_syllables: average(words.iter().map(|w| syllable::count_syllables(w, &classes)).sum()),
+    };
+
+    let lengths = &rules["word_length"];
+    Ok(Statistics {
+        letters: rules["alphabet"].first_key_value().unwrap().0.chars().count(),
+        patterns,
+        continuations,
+        forbidden,
+        wildcards,
+        terminating,
+        word_length: (lengths["min"], lengths["avg"], lengths["max"]),
+        sample,
+    })
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+
+    use rand::SeedableRng;
+
+    #[test]
+    fn rules_statistics_correct_values() {
+        let yaml = File::open("./assets/testing/test1.yaml").expect("YAML file not found.");
+        let rules: BTreeMap<String, BTreeMap<String, u32>> = serde_yaml::from_reader(yaml).expect("YAML file wrong format.");
+        let mut rng = Pcg32::seed_from_u64(0);
+        let result = rules_statistics(&mut rng, 20, &rules).expect("Failed to get statistics.");
+
+        assert_eq!(result.letters, 3);
+        assert_eq!(result.patterns, BTreeMap::from([(1, 4), (2, 1)]));
+        assert_eq!((result.continuations, result.forbidden, result.wildcards, result.terminating), (12, 1, 2, 1));
+        assert_eq!(result.word_length, (1, 3, 5));
+        assert_eq!(result.sample.words, 20);
+        assert!(result.sample.distinct <= 20 && result.sample.average_length >= 1.0);
+
+        let report = result.to_string();
+        assert!(report.starts_with("Letters: 3\nPatterns: 5\n  1 letters: 4\n  2 letters: 1\nContinuations: 12 (2.40 per pattern)\n"));
+        assert!(report.contains("Word length: min 1, avg 3, max 5\nSample of 20 words:"));
+    }
+}
+// TESTS END
@@ -0,0 +1,85 @@
+use crate::classes::Classes;
+
+// Splits a word into syllables.
+// Each run of vowels is the nucleus of a syllable. The consonants between two nuclei are split using the maximal onset
+// principle: the following syllable gets the longest onset whose sonority rises towards its nucleus, and the rest
+// is the coda of the preceding syllable. Words without vowels are a single syllable.
+pub fn syllabify(word: &str, classes: &Classes) -> Vec<String> {
+    let chars: Vec<char> = word.chars().collect();
+
+    // Find the start and end of each nucleus.
+    let mut nuclei: Vec<(usize, usize)> = Vec::new();
+    for (i, c) in chars.iter().enumerate() {
+        if !classes.is_vowel(*c) {
+            continue
+        }
+        match nuclei.last_mut() {
+            Some((_, end)) if *end == i => *end = i + 1,
+            _ => nuclei.push((i, i + 1)),
+        }
+    }
+    if nuclei.len() < 2 {
+        return if word.is_empty() { vec![] } else { vec![word.to_owned()] }
+    }
+
+    // Find where each syllable after the first one starts.
+    let mut starts = Vec::new();
+    for pair in nuclei.windows(2) {
+        let (coda_start, onset_end) = (pair[0].1, pair[1].0);
+        let mut start = onset_end;
+        while start > coda_start && rising(&chars[start - 1..onset_end], classes) {
+            start -= 1;
+        }
+        starts.push(start);
+    }
+
+    // Split the word at the start of each syllable.
+    let mut result = Vec::new();
+    let mut previous = 0;
+    for start in starts.into_iter().chain([chars.len()]) {
+        result.push(chars[previous..start].iter().collect());
+        previous = start;
+    }
+    result
+}
+
+// Joins the syllables of a word with hyphens, e.g. "ba-na-na".
+pub fn hyphenate(word: &str, classes: &Classes) -> String {
+    syllabify(word, classes).join("-")
+}
+
+// Counts the syllables of a word.
+pub fn count_syllables(word: &str, classes: &Classes) -> usize {
+    syllabify(word, classes).len()
+}
+
+// Whether the sonority of an onset strictly rises towards the nucleus.
+fn rising(onset: &[char], classes: &Classes) -> bool {
+    onset.windows(2).all(|pair| classes.sonority(pair[0]) < classes.sonority(pair[1]))
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syllabify_correct_values() {
+        let classes = Classes::from_alphabet("abcdefghijklmnopqrstuvwxyz");
+        assert_eq!(syllabify("banana", &classes), ["ba", "na", "na"]);
+        assert_eq!(syllabify("astra", &classes), ["as", "tra"]);
+        assert_eq!(syllabify("andre", &classes), ["an", "dre"]);
+        assert_eq!(syllabify("aorta", &classes), ["aor", "ta"]);
+        assert_eq!(syllabify("strength", &classes), ["strength"]);
+        assert_eq!(syllabify("hmm", &classes), ["hmm"]);
+        assert_eq!(syllabify("", &classes), Vec::<String>::new());
+    }
+
+    #[test]
+    fn hyphenate_correct_values() {
+        let classes = Classes::from_alphabet("abcdefghijklmnopqrstuvwxyz");
+        assert_eq!(hyphenate("elvenking", &classes), "el-ven-king");
+        assert_eq!(count_syllables("elvenking", &classes), 3);
+    }
+}
+// TESTS END
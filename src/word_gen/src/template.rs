@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+
+use crate::classes::Classes;
+use crate::generator::Language;
+use crate::rng::Pcg32;
+use crate::verification;
+
+// How many words are generated from the templates before giving up on finding one that the patterns can generate.
+const FILTER_ATTEMPTS: usize = 1000;
+
+// How many words are generated from the templates to pick the most likely one from.
+const SCORE_CANDIDATES: usize = 10;
+
+// How the words generated from templates are combined with the patterns of the rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combination {
+    // The patterns are not used.
+    None,
+    // Words that the patterns can't generate are discarded.
+    Filter,
+    // The word that the patterns are most likely to generate is picked from several candidates.
+    Score,
+}
+
+impl Combination {
+    // Returns the combination matching a name given as an argument, e.g. "filter".
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Self::None),
+            "filter" => Some(Self::Filter),
+            "score" => Some(Self::Score),
+            _ => None,
+        }
+    }
+}
+
+// A part of a template, e.g. "C" or the optional "(N)". Each slot of a part is filled with a unit from its inventory.
+#[derive(Debug, Clone, PartialEq)]
+struct Part {
+    slots: Vec<Vec<(String, u32)>>,
+    optional: bool,
+}
+
+// The syllable templates of a language, e.g. "(C)V(N)", filled from weighted inventories of onsets, nuclei and codas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Templates {
+    templates: Vec<(Vec<Part>, u32)>,
+}
+
+impl Templates {
+    // Creates the templates from the templates, onsets, nuclei and codas sections of the rules.
+    // In a template, V is filled from the nuclei, C from the onsets before V and from the codas after it,
+    // any other uppercase letter from the class with that name, and lowercase letters are used as they are.
+    // Parts in parentheses are optional. Without onsets, nuclei or codas, the consonants and vowels of the classes are used.
+    pub fn from_rules<'a>(rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Result<Self, &'a str> {
+        let section = rules.get("templates").ok_or("No templates in rules.")?;
+        let classes = Classes::from_rules(rules)?;
+        let mut alphabet = match rules.get("alphabet").and_then(|a| a.first_key_value()) {
+            Some((k, _)) => k.to_owned(),
+            None => return Err("No alphabet in rules."),
+        };
+        alphabet.push(' ');
+
+        let onsets = inventory(rules.get("onsets"), &classes, 'C')?;
+        let nuclei = inventory(rules.get("nuclei"), &classes, 'V')?;
+        let codas = inventory(rules.get("codas"), &classes, 'C')?;
+
+        let mut templates = Vec::new();
+        for (template, weight) in section {
+            let mut parts: Vec<Part> = Vec::new();
+            let mut optional = false;
+            let mut nucleus = false;
+
+            for c in template.chars() {
+                let slot = match c {
+                    '(' if !optional => {
+                        optional = true;
+                        parts.push(Part { slots: vec![], optional: true });
+                        continue
+                    }
+                    ')' if optional => {
+                        optional = false;
+                        continue
+                    }
+                    'V' => {
+                        nucleus = true;
+                        nuclei.clone()
+                    }
+                    'C' => if nucleus { codas.clone() } else { onsets.clone() },
+                    c if c.is_uppercase() => {
+                        let members = classes.get(c).ok_or("Template uses an undefined class.")?;
+                        members.iter().map(|m| (m.to_owned(), 1)).collect()
+                    }
+                    c if alphabet.contains(c) => vec![(c.to_string(), 1)],
+                    _ => return Err("Template is not made of classes, letters and parentheses."),
+                };
+
+                if optional {
+                    parts.last_mut().unwrap().slots.push(slot);
+                } else {
+                    parts.push(Part { slots: vec![slot], optional: false });
+                }
+            }
+
+            if optional || parts.iter().all(|p| p.slots.is_empty()) {
+                return Err("Template is empty or has unclosed parentheses.")
+            }
+            if *weight > 0 {
+                templates.push((parts, *weight));
+            }
+        }
+
+        // Verify every unit is written using only characters in the alphabet.
+        for unit in onsets.iter().chain(&nuclei).chain(&codas).map(|(u, _)| u) {
+            if !unit.chars().all(|c| alphabet.contains(c)) {
+                return Err("Character not in alphabet used.")
+            }
+        }
+        if templates.is_empty() {
+            return Err("No templates with weight in rules.")
+        }
+
+        Ok(Self { templates })
+    }
+
+    // Generates a word with the given number of syllables.
+    pub fn generate_word(&self, rng: &mut Pcg32, syllables: usize) -> String {
+        let mut result = String::new();
+        for _ in 0..syllables {
+            let template = pick(rng, &self.templates);
+            for part in template {
+                // Optional parts are used half of the time.
+                if part.optional && rng.below(2) == 0 {
+                    continue
+                }
+                for slot in &part.slots {
+                    result.push_str(pick::<String>(rng, slot));
+                }
+            }
+        }
+        result
+    }
+}
+
+// Generates amount number of words with the given number of syllables from the templates of the rules.
+// The words can be combined with the patterns of the rules, either discarding the words the patterns can't generate,
+// or picking the one the patterns are most likely to generate from several words.
+pub fn generate_words_from_templates<'a>(rng: &mut Pcg32, amount: u32, syllables: usize, rules: &'a BTreeMap<String, BTreeMap<String, u32>>, combination: Combination) -> Result<String, &'a str> {
+    let templates = Templates::from_rules(rules)?;
+    if syllables == 0 {
+        return Err("Syllables can't be 0.")
+    }
+
+    // The patterns are only needed when combining with them.
+    let language = if combination == Combination::None {
+        None
+    } else {
+        verification::verify_rules(rules)?;
+        Some(Language::new(rules))
+    };
+
+    let mut result = String::from("");
+    for _ in 0..amount {
+        let word = match (combination, &language) {
+            (Combination::Filter, Some(language)) => {
+                (0..FILTER_ATTEMPTS)
+                    .map(|_| templates.generate_word(rng, syllables))
+                    .find(|w| language.score(w).is_some())
+                    .ok_or("No word found.")?
+            }
+            (Combination::Score, Some(language)) => {
+                let mut best = (f32::NEG_INFINITY, String::new());
+                for _ in 0..SCORE_CANDIDATES {
+                    let word = templates.generate_word(rng, syllables);
+                    let score = language.score(&word).unwrap_or(f32::NEG_INFINITY);
+                    if best.1.is_empty() || score > best.0 {
+                        best = (score, word);
+                    }
+                }
+                best.1
+            }
+            _ => templates.generate_word(rng, syllables),
+        };
+        result.push_str(&word);
+        result.push(' ');
+    }
+
+    Ok(result.trim().to_owned())
+}
+
+// Gets the weighted units of an inventory section, or the members of a class if there is no such section.
+fn inventory<'a>(section: Option<&BTreeMap<String, u32>>, classes: &Classes, class: char) -> Result<Vec<(String, u32)>, &'a str> {
+    let result: Vec<(String, u32)> = match section {
+        Some(s) => s.iter().filter(|(_, w)| **w > 0).map(|(u, w)| (u.to_owned(), *w)).collect(),
+        None => classes.get(class).into_iter().flatten().map(|m| (m.to_owned(), 1)).collect(),
+    };
+    if result.is_empty() {
+        return Err("Inventory of templates is empty.")
+    }
+    Ok(result)
+}
+
+// Picks one of the items at random by weight.
+fn pick<'a, T>(rng: &mut Pcg32, items: &'a [(T, u32)]) -> &'a T {
+    let sum: u32 = items.iter().map(|(_, w)| w).sum();
+    let mut r = rng.below(sum);
+    for (item, weight) in items {
+        if r < *weight { return item }
+        r -= weight;
+    }
+    &items.last().unwrap().0
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::SeedableRng;
+
+    fn get_rules(templates: &[(&str, u32)]) -> BTreeMap<String, BTreeMap<String, u32>> {
+        BTreeMap::from([
+            ("alphabet".to_owned(), BTreeMap::from([("aikmnst".to_owned(), 0)])),
+            ("classes".to_owned(), BTreeMap::from([("N=mn".to_owned(), 0)])),
+            ("templates".to_owned(), templates.iter().map(|(t, w)| (t.to_string(), *w)).collect()),
+            ("onsets".to_owned(), BTreeMap::from([("k".to_owned(), 1), ("st".to_owned(), 1)])),
+            ("nuclei".to_owned(), BTreeMap::from([("a".to_owned(), 1), ("i".to_owned(), 0)])),
+        ])
+    }
+
+    #[test]
+    fn generate_word_follows_templates() {
+        let templates = Templates::from_rules(&get_rules(&[("CV(N)", 1)])).expect("Failed to read templates.");
+        let mut rng = Pcg32::seed_from_u64(0);
+
+        let mut codas = false;
+        for _ in 0..100 {
+            let word = templates.generate_word(&mut rng, 2);
+            let syllables: Vec<&str> = word.split_inclusive(['a', 'm', 'n']).filter(|s| *s != "m" && *s != "n").collect();
+            assert_eq!(syllables.len(), 2, "{word}");
+            for syllable in syllables {
+                assert!(syllable == "ka" || syllable == "sta", "{word}");
+            }
+            codas |= word.contains('m') || word.contains('n');
+        }
+        assert!(codas);
+    }
+
+    #[test]
+    fn from_rules_invalid_templates() {
+        for template in ["C(V", "CV)", "()", "CXV", "CV?"] {
+            assert!(Templates::from_rules(&get_rules(&[(template, 1)])).is_err(), "{template}");
+        }
+        assert!(Templates::from_rules(&get_rules(&[("CV", 0)])).is_err());
+        assert!(Templates::from_rules(&get_rules(&[("sV", 1), ("(C)V(N)", 2)])).is_ok());
+    }
+}
+// TESTS END
@@ -0,0 +1,116 @@
+use std::fmt;
+
+// Every step of generating a word, every candidate and which of them was chosen, to explain why a word was generated.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Trace {
+    pub steps: Vec<Step>,
+    pub candidates: Vec<Candidate>,
+    // The index of the chosen candidate, the first one with the highest value.
+    pub chosen: usize,
+    pub word: String,
+}
+
+// A step of generating a word: the pattern matching the end of the word so far, and the continuation picked for it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Step {
+    // The word so far, without the space at its start.
+    pub current: String,
+    // The longest pattern matching the end of the word so far, its length in letters being how many letters matched.
+    pub pattern: String,
+    // The number drawn from the cumulative weights of the continuations, excluding ending the word, tupled with
+    // the lowest and highest numbers it could be. None if there was only one continuation to pick.
+    pub draw: Option<(u32, u32, u32)>,
+    // The continuation picked, as in the rules, empty if the pattern can only end the word.
+    pub picked: String,
+    // The continuation after replacing its wildcards with random letters, if it had any.
+    pub wildcard: Option<String>,
+    // The continuation added to the word, after keeping it in harmony with the word.
+    pub continuation: String,
+    // The index of the candidate of the word after the step, if it was long enough to be one.
+    pub candidate: Option<usize>,
+}
+
+// A word that could be generated, valued by how close its length (or number of syllables) is to the target,
+// from 0 to 1, plus the chance of the word ending with the pattern it ends on.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Candidate {
+    pub word: String,
+    pub closeness: f32,
+    pub ending: f32,
+    pub value: f32,
+}
+
+impl Step {
+    // Whether the step ended the word, because its pattern can only end the word.
+    pub fn ended(&self) -> bool {
+        self.picked.is_empty()
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.word)?;
+        for (i, step) in self.steps.iter().enumerate() {
+            write!(f, "  {}. \"{}\" matched pattern \"{}\" of length {}", i + 1, step.current, step.pattern, step.pattern.chars().count())?;
+            if step.ended() {
+                writeln!(f, ", which can only end the word")?;
+                continue
+            }
+            match step.draw {
+                Some((draw, low, high)) => write!(f, ", drew {draw} of {low} to {high}")?,
+                None => write!(f, ", the only continuation")?,
+            }
+            write!(f, ", picked \"{}\"", step.picked)?;
+            if let Some(wildcard) = &step.wildcard {
+                write!(f, ", wildcard > \"{wildcard}\"")?;
+            }
+            if step.continuation != *step.wildcard.as_ref().unwrap_or(&step.picked) {
+                write!(f, ", harmony > \"{}\"", step.continuation)?;
+            }
+            if let Some(candidate) = step.candidate {
+                write!(f, ", candidate {}", candidate + 1)?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "  Candidates:")?;
+        for (i, candidate) in self.candidates.iter().enumerate() {
+            write!(f, "    {}. \"{}\": {:.3} (length {:.3} + ending {:.3})", i + 1, candidate.word, candidate.value, candidate.closeness, candidate.ending)?;
+            writeln!(f, "{}", if i == self.chosen { " < chosen" } else { "" })?;
+        }
+        let best = self.candidates.get(self.chosen).map_or(0.0, |c| c.value);
+        let tied = self.candidates.iter().filter(|c| c.value == best).count();
+        write!(f, "  Chose candidate {} with the highest value", self.chosen + 1)?;
+        if tied > 1 {
+            write!(f, ", the first of {tied} tied ones")?;
+        }
+        Ok(())
+    }
+}
+
+// TESTS BEGIN
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_correct_values() {
+        let trace = Trace {
+            steps: vec![
+                Step { current: String::new(), pattern: " ".to_owned(), draw: Some((2, 1, 4)), picked: "_a".to_owned(), wildcard: Some("ka".to_owned()), continuation: "ka".to_owned(), candidate: None },
+                Step { current: "ka".to_owned(), pattern: "a".to_owned(), draw: None, picked: "e".to_owned(), wildcard: None, continuation: "a".to_owned(), candidate: Some(0) },
+                Step { current: "kaa".to_owned(), pattern: "a".to_owned(), draw: None, picked: String::new(), wildcard: None, continuation: String::new(), candidate: None },
+            ],
+            candidates: vec![Candidate { word: "kaa".to_owned(), closeness: 0.5, ending: 0.25, value: 0.75 }],
+            chosen: 0,
+            word: "kaa".to_owned(),
+        };
+        let text = trace.to_string();
+        assert!(text.starts_with("kaa\n  1. \"\" matched pattern \" \" of length 1, drew 2 of 1 to 4, picked \"_a\", wildcard > \"ka\"\n"));
+        assert!(text.contains("  2. \"ka\" matched pattern \"a\" of length 1, the only continuation, picked \"e\", harmony > \"a\", candidate 1\n"));
+        assert!(text.contains("  3. \"kaa\" matched pattern \"a\" of length 1, which can only end the word\n"));
+        assert!(text.contains("    1. \"kaa\": 0.750 (length 0.500 + ending 0.250) < chosen\n"));
+        assert!(text.ends_with("Chose candidate 1 with the highest value"));
+    }
+}
+// TESTS END
@@ -1,5 +1,18 @@
 use std::collections::BTreeMap;
 
+use crate::classes::Classes;
+use crate::harmony::Harmony;
+use crate::orthography;
+use crate::prosody::Prosody;
+use crate::template::Templates;
+
+// Whether a key of the rules is a section with a special meaning, instead of a pattern.
+pub fn is_section(key: &str) -> bool {
+    matches!(key, "alphabet" | "word_length" | "classes" | "sonority" | "templates" | "onsets" | "nuclei" | "codas"
+        | "stress" | "tones" | "tone_sequences" | "harmony")
+        || orthography::is_section(key)
+}
+
 // Verifies the BTreeMap read from the yaml file to ensure it is valid and contains necessary information.
 pub fn verify_rules(rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Result<(), &str> {
     // Verify the alphabet is defined properly.
@@ -34,9 +47,37 @@ pub fn verify_rules(rules: &BTreeMap<String, BTreeMap<String, u32>>) -> Result<(
         None => return Err("No word_length in rules.")
     }
 
+    // Verify every unit in the orthographies is written using only characters in the alphabet.
+    for (_, orthography) in orthography::all(rules)? {
+        for unit in orthography.units() {
+            in_alphabet(unit, &alphabet)?;
+        }
+    }
+
+    // Verify the classes are defined properly, using only characters in the alphabet.
+    let classes = Classes::from_rules(rules)?;
+    for name in classes.names() {
+        for member in classes.get(*name).into_iter().flatten() {
+            in_alphabet(member, &alphabet)?;
+        }
+    }
+
+    // Verify the syllable templates are defined properly, if there are any.
+    if rules.contains_key("templates") {
+        Templates::from_rules(rules)?;
+    }
+
+    // Verify every letter in the harmony sets is in the alphabet.
+    for letter in Harmony::from_rules(rules)?.letters() {
+        in_alphabet(&letter.to_string(), &alphabet)?;
+    }
+
+    // Verify the stress system and tones are defined properly.
+    Prosody::from_rules(rules)?;
+
     // Verify every letter rule is defined using only characters in the alphabet.
     for (k, v) in rules {
-        if k == "alphabet" || k == "word_length" {
+        if is_section(k) {
             continue
         }
 
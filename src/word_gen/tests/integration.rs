@@ -1,10 +1,34 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::File;
+use std::path::{Path, PathBuf};
 
-use rand::{rngs::StdRng, SeedableRng};
+use rand::SeedableRng;
 
-use word_gen::generator::generate_words;
-use word_gen::reader::rules_from_string;
+use word_gen::comparison::compare_rules;
+use word_gen::continuum::generate_continuum;
+use word_gen::extraction::{extract_text, Format};
+use word_gen::blend::blend_rules;
+use word_gen::classes::Classes;
+use word_gen::command;
+use word_gen::family::generate_family;
+use word_gen::generator::{explain_words, explain_words_at, generate_records, generate_records_at, generate_rendered_words, generate_spelled_words, generate_word_at, generate_words, generate_words_at, generate_words_with_syllables, generate_words_at_with_syllables, score_words};
+use word_gen::identification::{identify, identify_words};
+use word_gen::naming::Namer;
+use word_gen::nativization::{nativize_word, nativize_words};
+use word_gen::output::{format_records, OutputFormat};
+use word_gen::paths::Resolver;
+use word_gen::phonology::{generate_rules, Phonology, Preset};
+use word_gen::provenance::Provenance;
+use word_gen::pronunciation::rules_from_dictionary;
+use word_gen::prosody::{Prosody, STRESS_MARK};
+use word_gen::harmony::Harmony;
+use word_gen::rng::{Pcg32, ALGORITHM_VERSION};
+use word_gen::reader::{harmony_from_string, rules_from_string};
+use word_gen::seed::{replay_command, seed_from_phrase, SeedNode};
+use word_gen::sound_change::{apply_changes, parse_changes};
+use word_gen::statistics::rules_statistics;
+use word_gen::syllable::count_syllables;
+use word_gen::template::{generate_words_from_templates, Combination};
 use word_gen::verification::verify_rules;
 
 fn get_rules(path: &str) -> BTreeMap<String, BTreeMap<String, u32>> {
@@ -80,10 +104,19 @@ fn rules_from_string_correct_values() {
     assert_eq!(rules_from_string(text, 3), rules);
 }
 
+#[test]
+fn rules_from_epub_correct_values() {
+    // The epub contains the same text as above, split over two documents and surrounded by markup.
+    let bytes = std::fs::read("./assets/testing/test12.epub").expect("EPUB file not found.");
+    let text = extract_text(&bytes, Format::Epub).expect("Failed to extract text.");
+    let rules = get_rules("test11");
+    assert_eq!(rules_from_string(&text, 3), rules);
+}
+
 #[test]
 fn generate_words_correct_values() {
     let rules = get_rules("test1");
-    let mut rng = StdRng::seed_from_u64(0);
+    let mut rng = Pcg32::seed_from_u64(0);
     let amount = 10;
 
     let result = generate_words(&mut rng, amount, &rules).expect("Failed to generate words.");
@@ -92,12 +125,649 @@ fn generate_words_correct_values() {
     assert_eq!(as_vec.len(), amount as usize);
 }
 
+#[test]
+fn generate_words_with_syllables_correct_values() {
+    let text = "lorem ipsum dolor sit amet consectetur adipiscing elit sed do eiusmod tempor incididunt ut labore et dolore magna aliqua";
+    let rules = rules_from_string(text, 3);
+    let classes = Classes::from_rules(&rules).expect("Failed to read classes.");
+    let count = |words: &str, syllables: usize| words.split(' ').filter(|w| count_syllables(w, &classes) == syllables).count();
+
+    // Aiming for a number of syllables gives more words with that many syllables than aiming for the average length.
+    let plain = generate_words(&mut Pcg32::seed_from_u64(0), 100, &rules).expect("Failed to generate words.");
+    for syllables in 1..=3 {
+        let result = generate_words_with_syllables(&mut Pcg32::seed_from_u64(0), 100, syllables, &rules).expect("Failed to generate words.");
+        assert!(count(&result, syllables) > count(&plain, syllables), "{syllables}: {result}");
+    }
+}
+
+#[test]
+fn generate_words_from_templates_correct_values() {
+    let rules = get_rules("test15");
+    verify_rules(&rules).expect("Failed to verify rules.");
+    let classes = Classes::from_rules(&rules).expect("Failed to read classes.");
+
+    for combination in [Combination::None, Combination::Filter, Combination::Score] {
+        let mut rng = Pcg32::seed_from_u64(0);
+        let result = generate_words_from_templates(&mut rng, 20, 3, &rules, combination).expect("Failed to generate words.");
+        for word in result.split(' ') {
+            assert_eq!(count_syllables(word, &classes), 3, "{word}");
+        }
+
+        // The patterns only allow n after e and i, and never m and n after each other.
+        if combination == Combination::Filter {
+            assert!(!result.contains("an") && !result.contains("em") && !result.contains("im"), "{result}");
+        }
+    }
+}
+
+#[test]
+fn generate_rendered_words_correct_values() {
+    let rules = get_rules("test16");
+    let prosody = Prosody::from_rules(&rules).expect("Failed to read prosody.");
+
+    let mut rng = Pcg32::seed_from_u64(0);
+    let words = generate_rendered_words(&mut rng, 50, &rules, "-").expect("Failed to generate words.");
+    for (word, rendering) in words {
+        let syllables = prosody.annotate(&word);
+        let stressed: Vec<usize> = syllables.iter().enumerate().filter(|(_, s)| s.stressed).map(|(i, _)| i).collect();
+        assert_eq!(stressed.len(), 1, "{rendering}");
+
+        // Stress is on the penultimate syllable, or the antepenultimate if the penultimate is light.
+        if syllables.len() > 1 {
+            let penultimate = &syllables[syllables.len() - 2].syllable;
+            let heavy = penultimate.ends_with(|c| !"aei".contains(c));
+            let expected = if heavy { syllables.len() - 2 } else { syllables.len().saturating_sub(3) };
+            assert_eq!(stressed[0], expected, "{rendering}");
+            assert_eq!(rendering.matches(STRESS_MARK).count(), 1, "{rendering}");
+        }
+
+        // No two high tones in a row, and never a low tone at the end.
+        let tones: Vec<&str> = syllables.iter().map(|s| s.tone.as_deref().unwrap()).collect();
+        assert!(!tones.windows(2).any(|t| t == ["H", "H"]) && tones.last() != Some(&"L"), "{rendering}");
+        assert_eq!(rendering.replace([STRESS_MARK, '-', '\u{301}', '\u{300}'], ""), word);
+    }
+}
+
+#[test]
+fn generate_words_with_harmony_correct_values() {
+    let text = std::fs::read_to_string("./assets/testing/test17.txt").expect("Sample text not found.");
+
+    // The sample text has front and back vowel harmony, with e and i neutral.
+    let (tendencies, systems) = harmony_from_string(&text);
+    assert!(tendencies.iter().any(|t| t.first == 'a' && t.second == 'ä' && t.ratio == 0.0));
+    assert_eq!(systems.len(), 1);
+    let (first, second) = systems[0].split_once(" | ").expect("Harmony not in the right form.");
+    let (mut first, mut second): (Vec<char>, Vec<char>) = (first.chars().collect(), second.chars().collect());
+    first.sort();
+    second.sort();
+    assert_eq!(first.into_iter().chain(['|']).chain(second).collect::<String>(), "aou|yäö");
+
+    // With the detected harmony, every generated word is harmonic.
+    let mut rules = rules_from_string(&text, 3);
+    rules.insert("harmony".to_owned(), BTreeMap::from([(systems[0].clone(), 0)]));
+    let harmony = Harmony::from_rules(&rules).expect("Failed to read harmony.");
+    let mut rng = Pcg32::seed_from_u64(0);
+    let result = generate_words(&mut rng, 100, &rules).expect("Failed to generate words.");
+    assert!(result.split(' ').all(|w| harmony.is_harmonic(w)), "{result}");
+
+    // Without harmony, the patterns mix front and back vowels in longer words.
+    rules.remove("harmony");
+    let mut rng = Pcg32::seed_from_u64(0);
+    let result = generate_words(&mut rng, 100, &rules).expect("Failed to generate words.");
+    assert!(!result.split(' ').all(|w| harmony.is_harmonic(w)), "{result}");
+}
+
+#[test]
+fn apply_changes_correct_values() {
+    let rules = get_rules("test15");
+    let classes = Classes::from_rules(&rules).expect("Failed to read classes.");
+    let text = std::fs::read_to_string("./assets/testing/test18.txt").expect("Sound changes file not found.");
+    let changes = parse_changes(&text, &classes).expect("Failed to parse sound changes.");
+    assert_eq!(changes.len(), 4);
+
+    let words = ["kiki", "kata", "amka", "sakena", "stan", "tima"];
+    let result = apply_changes(&words, &changes);
+    let after: Vec<&str> = result.iter().map(|(_, a)| a.as_str()).collect();
+    assert_eq!(after, ["tʃitʃi", "kada", "aŋka", "satʃen", "stan", "tim"]);
+
+    // Every generated word is changed the same way.
+    let mut rng = Pcg32::seed_from_u64(0);
+    let generated = generate_words(&mut rng, 50, &rules).expect("Failed to generate words.");
+    let generated: Vec<&str> = generated.split(' ').collect();
+    for (before, after) in apply_changes(&generated, &changes) {
+        assert!(!after.contains("ki") && !after.contains("ke") && !after.contains("mk") && !after.contains("nk"), "{before} > {after}");
+    }
+}
+
+#[test]
+fn generate_family_correct_values() {
+    let rules = get_rules("test15");
+    let classes = Classes::from_rules(&rules).expect("Failed to read classes.");
+    let mut rng = Pcg32::seed_from_u64(0);
+    let family = generate_family(&mut rng, 30, 3, 4, &rules).expect("Failed to generate family.");
+    assert_eq!(family.daughters.len(), 3);
+
+    // Every daughter language derives its words from the proto-language by its sound changes, and can generate new ones.
+    let proto: Vec<&str> = family.proto.iter().map(|w| w.as_str()).collect();
+    for daughter in &family.daughters {
+        assert_eq!(daughter.changes.len(), 4);
+        let changes = parse_changes(&daughter.changes.join("\n"), &classes).expect("Failed to parse sound changes.");
+        let words: Vec<String> = apply_changes(&proto, &changes).into_iter().map(|(_, w)| w).collect();
+        assert_eq!(words, daughter.words);
+        assert_ne!(daughter.words, family.proto, "{}", daughter.changes.join(", "));
+
+        let mut rng = Pcg32::seed_from_u64(0);
+        generate_words(&mut rng, 10, &daughter.rules).expect("Failed to generate words.");
+    }
+
+    let cognates = family.cognates();
+    assert_eq!(cognates.len(), family.proto.len());
+    assert!(cognates.iter().all(|row| row.len() == 4));
+
+    // The same seed generates the same family.
+    let mut rng = Pcg32::seed_from_u64(0);
+    assert_eq!(generate_family(&mut rng, 30, 3, 4, &rules), Ok(family));
+}
+
+#[test]
+fn generate_rules_correct_values() {
+    for preset in [Preset::Balanced, Preset::Polynesian, Preset::Germanic, Preset::Bantu] {
+        let phonology = Phonology::from_preset(preset);
+        for seed in 0..3 {
+            let mut rng = Pcg32::seed_from_u64(seed);
+            let rules = generate_rules(&mut rng, &phonology).expect("Failed to generate rules.");
+            verify_rules(&rules).expect("Failed to verify rules.");
+            let alphabet = rules["alphabet"].first_key_value().unwrap().0;
+            assert_eq!(alphabet.len(), phonology.consonants + phonology.vowels, "{preset:?}");
+            assert!(rules.values().any(|continuations| continuations.contains_key("_")));
+
+            // Words generated from the rules use the wildcard now and then.
+            let traces = explain_words(&mut Pcg32::seed_from_u64(seed), 500, None, &rules).expect("Failed to explain words.");
+            assert!(traces.iter().flat_map(|t| &t.steps).any(|s| s.wildcard.is_some()), "{preset:?}");
+
+            // The same seed synthesizes the same rules, and the rules generate words.
+            let mut rng = Pcg32::seed_from_u64(seed);
+            assert_eq!(generate_rules(&mut rng, &phonology), Ok(rules.clone()));
+            generate_words(&mut rng, 10, &rules).expect("Failed to generate words.");
+        }
+    }
+
+    // Different seeds synthesize different languages, and knobs are checked.
+    let phonology = Phonology::from_preset(Preset::Balanced);
+    let first = generate_rules(&mut Pcg32::seed_from_u64(0), &phonology);
+    assert_ne!(first, generate_rules(&mut Pcg32::seed_from_u64(1), &phonology));
+    let phonology = Phonology { consonants: 4, vowels: 2, complexity: 1, ..phonology };
+    let rules = generate_rules(&mut Pcg32::seed_from_u64(0), &phonology).expect("Failed to generate rules.");
+    assert_eq!(rules["alphabet"].first_key_value().unwrap().0.len(), 6);
+}
+
+#[test]
+fn blend_rules_correct_values() {
+    let first = get_rules("test15");
+    let second = get_rules("test14");
+    let rules = blend_rules(&[&first, &second], &[2, 1]).expect("Failed to blend rules.");
+    verify_rules(&rules).expect("Failed to verify rules.");
+
+    // The blend has the letters and patterns of both, and the sections of the heavier one.
+    let alphabet = rules["alphabet"].first_key_value().unwrap().0;
+    assert!(alphabet.contains('ʃ') && alphabet.contains('l'));
+    assert!(rules.contains_key("templates") && !rules.contains_key("orthography"));
+    let mut rng = Pcg32::seed_from_u64(0);
+    let words = generate_words(&mut rng, 100, &rules).expect("Failed to generate words.");
+    assert!(words.contains('ʃ') && words.contains('l'), "{words}");
+
+    // Blending rules with themselves keeps the probabilities of their continuations.
+    let rules = blend_rules(&[&first, &first], &[1, 3]).expect("Failed to blend rules.");
+    assert_eq!(rules["a"][" "] * first["a"]["k"], rules["a"]["k"] * first["a"][" "]);
+}
+
+#[test]
+fn generate_continuum_correct_values() {
+    let first = get_rules("test15");
+    let second = get_rules("test1");
+    let mut rng = Pcg32::seed_from_u64(0);
+    let continuum = generate_continuum(&mut rng, &[&first, &second], 6, 0.1).expect("Failed to generate continuum.");
+    assert_eq!(continuum.languages.len(), 6);
+    for rules in &continuum.languages {
+        verify_rules(rules).expect("Failed to verify rules.");
+    }
+
+    // Neighbors have closer probabilities of continuations than the ends do.
+    let distance = |a: usize, b: usize| -> f32 {
+        let (a, b) = (&continuum.languages[a], &continuum.languages[b]);
+        let probabilities = |rules: &BTreeMap<String, BTreeMap<String, u32>>, pattern: &str| -> BTreeMap<String, f32> {
+            let continuations = rules.get(pattern).cloned().unwrap_or_default();
+            let sum: u32 = continuations.values().sum();
+            continuations.into_iter().map(|(c, w)| (c, w as f32 / sum.max(1) as f32)).collect()
+        };
+        let patterns: Vec<&String> = a.keys().chain(b.keys()).filter(|k| k.as_str() != "alphabet" && k.as_str() != "word_length").collect();
+        patterns.into_iter().map(|p| {
+            let (pa, pb) = (probabilities(a, p), probabilities(b, p));
+            pa.keys().chain(pb.keys()).map(|c| (pa.get(c).unwrap_or(&0.0) - pb.get(c).unwrap_or(&0.0)).abs()).sum::<f32>()
+        }).sum()
+    };
+    assert!(distance(0, 1) < distance(0, 5) && distance(4, 5) < distance(0, 5));
+
+    // The lexicon has a word at each index in every language, and is the same for the same seed.
+    let lexicon = continuum.lexicon(0, 20).expect("Failed to generate lexicon.");
+    assert!(lexicon.iter().all(|words| words.len() == 20));
+    assert_eq!(continuum.lexicon(0, 20), Ok(lexicon.clone()));
+
+    // Each word is the word at its index of the seed in its language.
+    for (rules, words) in continuum.languages.iter().zip(&lexicon) {
+        let each: Vec<String> = (0..20).map(|i| generate_word_at(0, i, rules).expect("Failed to generate word.")).collect();
+        assert_eq!(*words, each);
+    }
+}
+
+#[test]
+fn generate_spelled_words_correct_values() {
+    let text = std::fs::read_to_string("./assets/testing/test13.txt").expect("Dictionary file not found.");
+    let rules = rules_from_dictionary(&text, 3).expect("Failed to read dictionary.");
+    let orthography = rules.get("orthography").expect("No orthography in rules.");
+    assert_eq!(orthography.get("tʃ > ch"), Some(&4));
+    assert_eq!(orthography.get("k > ck"), Some(&6));
+
+    let mut rng = Pcg32::seed_from_u64(0);
+    let words = generate_spelled_words(&mut rng, 10, &rules, None).expect("Failed to generate words.");
+    assert_eq!(words.len(), 10);
+
+    // Every spelling only uses letters of the dictionary words.
+    for (_, spelling) in words {
+        assert!(spelling.chars().all(|c| "abceiknpsht".contains(c)), "Unexpected spelling {spelling}");
+    }
+}
+
+#[test]
+fn generate_spelled_words_same_words_in_every_orthography() {
+    let rules = get_rules("test14");
+    let scholarly = generate_spelled_words(&mut Pcg32::seed_from_u64(0), 10, &rules, Some("scholarly")).expect("Failed to generate words.");
+    let popular = generate_spelled_words(&mut Pcg32::seed_from_u64(0), 10, &rules, Some("popular")).expect("Failed to generate words.");
+    let plain = generate_words(&mut Pcg32::seed_from_u64(0), 10, &rules).expect("Failed to generate words.");
+
+    for ((s, p), word) in scholarly.iter().zip(&popular).zip(plain.split(' ')) {
+        assert_eq!(s.0, word);
+        assert_eq!(p.0, word);
+        assert_eq!(s.1, word.replace('ʃ', "š"));
+        assert!(!p.1.contains('k') || p.1.contains("ck"));
+    }
+}
+
 #[test]
 #[should_panic]
 fn generate_words_invalid_yaml() {
     let rules = get_rules("test0");
-    let mut rng = StdRng::seed_from_u64(0);
+    let mut rng = Pcg32::seed_from_u64(0);
     let amount = 10;
 
     generate_words(&mut rng, amount, &rules).expect("");
 }
+
+#[test]
+fn nativize_words_correct_values() {
+    let rules = get_rules("test15");
+    let alphabet = rules["alphabet"].first_key_value().unwrap().0.to_owned();
+    let words = nativize_words(&["computer", "pizza", "Strength"], &rules).expect("Failed to nativize words.");
+    assert_eq!(words.len(), 3);
+
+    // Native words only use the alphabet, and stay the same when adapted again.
+    for (foreign, native) in &words {
+        assert!(native.chars().all(|c| alphabet.contains(c)), "{foreign} > {native}");
+        assert_eq!(nativize_word(native, &rules).as_ref(), Ok(native));
+    }
+
+    // Words the language can generate are already native.
+    let mut rng = Pcg32::seed_from_u64(0);
+    let generated = generate_words(&mut rng, 10, &rules).expect("Failed to generate words.");
+    for word in generated.split(' ') {
+        assert_eq!(nativize_word(word, &rules), Ok(word.to_owned()));
+    }
+}
+
+#[test]
+fn identify_correct_values() {
+    let first = get_rules("test15");
+    let second = get_rules("test14");
+    let models = [&first, &second];
+
+    // Words generated by each language are identified as that language.
+    for (i, rules) in models.iter().enumerate() {
+        let mut rng = Pcg32::seed_from_u64(0);
+        let words = generate_words(&mut rng, 20, rules).expect("Failed to generate words.");
+        let confidences = identify(&words, &models).expect("Failed to identify text.");
+        assert!(confidences[i] > 0.99, "{confidences:?}");
+        assert!((confidences.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+
+        let identified = identify_words(&words, &models).expect("Failed to identify words.");
+        assert_eq!(identified.len(), 20);
+        assert!(identified.iter().filter(|(_, c)| c[i] > 0.5).count() >= 15);
+    }
+}
+
+#[test]
+fn compare_rules_correct_values() {
+    let first = get_rules("test15");
+    let second = get_rules("test14");
+    let comparison = compare_rules(&first, &second).expect("Failed to compare rules.");
+    assert_eq!(comparison.second_letters, "ʃ");
+    assert!(comparison.first_patterns.contains(&"t".to_owned()) && comparison.second_patterns.contains(&"ʃ".to_owned()));
+    assert!(comparison.shifts.windows(2).all(|s| s[0].divergence >= s[1].divergence));
+    assert!(comparison.divergence > 0.0 && comparison.divergence < 1.0);
+
+    // The divergence is symmetric, and rules diverge less from a blend of themselves with others.
+    let reverse = compare_rules(&second, &first).expect("Failed to compare rules.");
+    assert!((comparison.divergence - reverse.divergence).abs() < 1e-6);
+    let blended = blend_rules(&[&first, &second], &[3, 1]).expect("Failed to blend rules.");
+    let closer = compare_rules(&first, &blended).expect("Failed to compare rules.");
+    assert!(closer.divergence < comparison.divergence);
+}
+
+#[test]
+fn score_words_correct_values() {
+    let rules = get_rules("test15");
+    let mut rng = Pcg32::seed_from_u64(0);
+    let words = generate_words(&mut rng, 10, &rules).expect("Failed to generate words.");
+    let mut words: Vec<&str> = words.split(' ').collect();
+    words.push("ʃʃʃ");
+
+    let scores = score_words(&words, &rules).expect("Failed to score words.");
+    assert_eq!(scores.len(), 11);
+    assert!(scores[..10].iter().all(|(_, s)| s.is_some_and(|s| s < 0.0)));
+    assert_eq!(scores[10], ("ʃʃʃ".to_owned(), None));
+    assert!(score_words(&words, &BTreeMap::new()).is_err());
+}
+
+#[test]
+fn rules_statistics_correct_values() {
+    let rules = get_rules("test15");
+    let mut rng = Pcg32::seed_from_u64(0);
+    let stats = rules_statistics(&mut rng, 100, &rules).expect("Failed to get statistics.");
+    assert_eq!(stats.letters, 9);
+    assert_eq!(stats.word_length, (2, 5, 10));
+    assert_eq!(stats.sample.words, 100);
+    assert!(stats.sample.distinct > 1 && stats.sample.distinct <= 100);
+    assert!((2.0..=10.0).contains(&stats.sample.average_length));
+
+    // The same seed gives the same statistics.
+    let mut rng = Pcg32::seed_from_u64(0);
+    assert_eq!(rules_statistics(&mut rng, 100, &rules), Ok(stats));
+}
+
+#[test]
+fn resolve_correct_values() {
+    let resolver = Resolver::from_environment(&[PathBuf::from("./assets/testing")]);
+
+    // Files are found in the given directories first, then in the built-in examples.
+    assert_eq!(resolver.resolve("test1.yaml"), Ok(Path::new("./assets/testing").join("test1.yaml")));
+    let example = resolver.resolve("example.yaml").expect("Failed to resolve example.");
+    assert!(example.ends_with(Path::new("assets").join("examples").join("example.yaml")));
+
+    let error = resolver.resolve("missing.yaml").unwrap_err();
+    assert_eq!(error.tried.len(), resolver.directories.len() + 1);
+    assert_eq!(error.tried[1], Path::new("./assets/testing").join("missing.yaml"));
+}
+
+#[test]
+fn provenance_correct_values() {
+    let text = std::fs::read("./assets/testing/test17.txt").expect("Text file not found.");
+    let rules = rules_from_string(&String::from_utf8_lossy(&text), 3);
+    let provenance = Provenance::new("test17.txt", &text, 3);
+    assert_eq!(provenance, Provenance::new("test17.txt", &text, 3));
+    assert_ne!(provenance.hash, Provenance::new("test17.txt", b"other", 3).hash);
+
+    // The annotated rules can still be read as they are.
+    let yaml = provenance.annotate(&serde_yaml::to_string(&rules).expect("Failed to write rules."));
+    assert!(yaml.starts_with("# Learned by word_gen from \"test17.txt\"."));
+    let read: BTreeMap<String, BTreeMap<String, u32>> = serde_yaml::from_str(&yaml).expect("Failed to read rules.");
+    assert_eq!(read, rules);
+}
+
+#[test]
+fn seed_from_phrase_correct_values() {
+    let rules = get_rules("test15");
+    let generate = |seed: u64| {
+        let mut rng = Pcg32::seed_from_u64(seed);
+        generate_words(&mut rng, 10, &rules).expect("Failed to generate words.")
+    };
+
+    // The same phrase always generates the same words, and a different one other words.
+    assert_eq!(generate(seed_from_phrase("middle earth")), generate(seed_from_phrase("middle earth")));
+    assert_ne!(generate(seed_from_phrase("middle earth")), generate(seed_from_phrase("narnia")));
+
+    let args: Vec<String> = ["word_gen", "--seed-phrase", "middle earth", "-r", "test15.yaml"].iter().map(|a| a.to_string()).collect();
+    let seed = seed_from_phrase("middle earth");
+    assert_eq!(replay_command(&args, seed), format!("word_gen -r test15.yaml --seed {seed}"));
+}
+
+// Golden tests locking the words generated for seeds, which must never change unless the version of the rng
+// algorithm is bumped.
+#[test]
+fn generate_words_golden_values() {
+    let yaml = File::open("./assets/examples/example.yaml").expect("YAML file not found.");
+    let example: BTreeMap<String, BTreeMap<String, u32>> = serde_yaml::from_reader(yaml).expect("YAML file wrong format.");
+    let generate = |seed: u64, rules: &BTreeMap<String, BTreeMap<String, u32>>| {
+        let mut rng = Pcg32::seed_from_u64(seed);
+        generate_words(&mut rng, 10, rules).expect("Failed to generate words.")
+    };
+
+    assert_eq!(ALGORITHM_VERSION, 1);
+    assert_eq!(generate(0, &example), "llaret nedssy ryhome elerit saryle rilemig nromop alamra phugmn mmrury");
+    assert_eq!(generate(42, &example), "mripsk elilem mmbbid ettskm gebllo aplasr nephna gegipl sopude numdnu");
+    assert_eq!(generate(42, &get_rules("test15")), "kekes alal asel esal enlas akak isam lasak isak akal");
+    assert_eq!(generate(7, &get_rules("test1")), "ca ca ba ba bb ca ba ba ba ba");
+
+    let mut rng = Pcg32::seed_from_u64(42);
+    let words = generate_words_from_templates(&mut rng, 10, 2, &get_rules("test15"), Combination::None).expect("Failed to generate words.");
+    assert_eq!(words, "stinsa lemle leti kita tise tike kili tasam lata sese");
+}
+
+#[test]
+fn generate_word_at_correct_values() {
+    let yaml = File::open("./assets/examples/example.yaml").expect("YAML file not found.");
+    let example: BTreeMap<String, BTreeMap<String, u32>> = serde_yaml::from_reader(yaml).expect("YAML file wrong format.");
+
+    // Word 5000 of seed 42, which must never change unless the version of the rng algorithm is bumped.
+    assert_eq!(generate_word_at(42, 5000, &example), Ok("edipab".to_owned()));
+    assert_eq!(generate_words_at(42, 4998, 3, &example), Ok("gittnla nomuta edipab".to_owned()));
+
+    // The words of a range are the words at each of its indexes.
+    let rules = get_rules("test15");
+    let words = generate_words_at(7, 0, 20, &rules).expect("Failed to generate words.");
+    let each: Vec<String> = (0..20).map(|i| generate_word_at(7, i, &rules).expect("Failed to generate word.")).collect();
+    assert_eq!(words, each.join(" "));
+}
+
+#[test]
+fn namer_correct_values() {
+    let yaml = File::open("./assets/examples/example.yaml").expect("YAML file not found.");
+    let example: BTreeMap<String, BTreeMap<String, u32>> = serde_yaml::from_reader(yaml).expect("YAML file wrong format.");
+
+    // Names which must never change unless the version of the rng algorithm is bumped.
+    let namer = Namer::from_rules(&example, "arda").expect("Failed to create namer.");
+    assert_eq!(namer.name_for("npc:1234"), Ok("elilat".to_owned()));
+    assert_eq!(Namer::from_rules(&example, "").unwrap().name_for("npc:1"), Ok("alonap".to_owned()));
+
+    // Names don't depend on the order of the keys, unless they have to avoid the names of the keys before them.
+    let names = namer.names_for(&["npc:2", "npc:1", "npc:1234"], &BTreeSet::new()).expect("Failed to get names.");
+    assert_eq!(names, ["ehsllo", "nomesar", "elilat"]);
+
+    // Too few possible words to name every key.
+    let rules = get_rules("test1");
+    let keys: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+    let keys: Vec<&str> = keys.iter().map(|k| k.as_str()).collect();
+    assert!(Namer::from_rules(&rules, "").unwrap().names_for(&keys, &BTreeSet::new()).is_err());
+}
+
+#[test]
+fn seed_tree_regenerates_hierarchy() {
+    // A language for each region of a world, and the names of the towns of each region, all from the root seed.
+    let hierarchy = |root: u64| {
+        let world = SeedNode::root(root).child("world");
+        let mut result = Vec::new();
+        for region in ["north", "south"] {
+            let node = world.child(region);
+            let rules = generate_rules(&mut node.rng(), &Phonology::from_preset(Preset::Balanced)).expect("Failed to generate rules.");
+            for town in 0..3 {
+                let town = node.child(&format!("town-{town}"));
+                result.push(generate_word_at(town.seed, 0, &rules).expect("Failed to generate word."));
+            }
+        }
+        result
+    };
+
+    let names = hierarchy(42);
+    assert_eq!(names, hierarchy(42));
+    assert_ne!(names, hierarchy(43));
+
+    // A town can be addressed directly by its path.
+    let north = SeedNode::root(42).descend("world/north").expect("Failed to descend.");
+    let rules = generate_rules(&mut north.rng(), &Phonology::from_preset(Preset::Balanced)).expect("Failed to generate rules.");
+    let town = SeedNode::root(42).descend("world/north/town-2").expect("Failed to descend.");
+    assert_eq!(generate_word_at(town.seed, 0, &rules), Ok(names[2].clone()));
+}
+
+#[test]
+fn generate_records_correct_values() {
+    let rules = get_rules("test15");
+    let records = generate_records_at(42, 10, 5, None, &rules).expect("Failed to generate records.");
+    let words = generate_words_at(42, 10, 5, &rules).expect("Failed to generate words.");
+    assert_eq!(records.iter().map(|r| r.word.as_str()).collect::<Vec<_>>().join(" "), words);
+
+    let words: Vec<&str> = words.split(' ').collect();
+    let scores = score_words(&words, &rules).expect("Failed to score words.");
+    for ((record, index), (_, score)) in records.iter().zip(10..).zip(scores) {
+        assert_eq!((record.seed, record.index, record.score), (Some(42), Some(index), score));
+        assert_eq!((record.length, record.version), (record.word.chars().count(), ALGORITHM_VERSION));
+        let ending = record.ending.as_deref().expect("Word without an ending pattern.");
+        assert!(format!(" {}", record.word).ends_with(ending));
+    }
+
+    // Every record has the seed and index of its word, whatever the range it was generated in.
+    let record = generate_records_at(42, 12, 1, None, &rules).expect("Failed to generate records.");
+    assert_eq!(record[0], records[2]);
+    assert_eq!(generate_word_at(42, 12, &rules), Ok(record[0].word.clone()));
+    let records_with_syllables = generate_records_at(42, 10, 5, Some(2), &rules).expect("Failed to generate records.");
+    let words_with_syllables = generate_words_at_with_syllables(42, 10, 5, 2, &rules).expect("Failed to generate words.");
+    assert_eq!(records_with_syllables.iter().map(|r| r.word.as_str()).collect::<Vec<_>>().join(" "), words_with_syllables);
+
+    // Records of words generated one after another are the same words as generate_words, without a seed or index.
+    let sequential = generate_records(&mut Pcg32::seed_from_u64(42), 10, None, &rules).expect("Failed to generate records.");
+    let words = generate_words(&mut Pcg32::seed_from_u64(42), 10, &rules).expect("Failed to generate words.");
+    assert_eq!(sequential.iter().map(|r| r.word.as_str()).collect::<Vec<_>>().join(" "), words);
+    assert!(sequential.iter().all(|r| r.seed.is_none() && r.index.is_none()));
+
+    // One line per record, after the header of csv.
+    assert_eq!(format_records(&records, OutputFormat::JsonLines).lines().count(), 5);
+    assert_eq!(format_records(&records, OutputFormat::Csv).lines().count(), 6);
+}
+
+#[test]
+fn explain_words_matches_generated_words() {
+    let yaml = File::open("./assets/examples/example.yaml").expect("YAML file not found.");
+    let example: BTreeMap<String, BTreeMap<String, u32>> = serde_yaml::from_reader(yaml).expect("YAML file wrong format.");
+
+    // Explaining words doesn't change them.
+    let traces = explain_words(&mut Pcg32::seed_from_u64(42), 10, None, &example).expect("Failed to explain words.");
+    let words: Vec<&str> = traces.iter().map(|t| t.word.as_str()).collect();
+    assert_eq!(words.join(" "), "mripsk elilem mmbbid ettskm gebllo aplasr nephna gegipl sopude numdnu");
+
+    let traces = explain_words_at(42, 5000, 1, None, &example).expect("Failed to explain words.");
+    assert_eq!(traces[0].word, "edipab");
+
+    let rules = get_rules("test15");
+    let traces = explain_words(&mut Pcg32::seed_from_u64(3), 10, Some(2), &rules).expect("Failed to explain words.");
+    let words = generate_words_with_syllables(&mut Pcg32::seed_from_u64(3), 10, 2, &rules).expect("Failed to generate words.");
+    assert_eq!(traces.iter().map(|t| t.word.as_str()).collect::<Vec<_>>().join(" "), words);
+
+    // Each word is built up by its steps, and is the first candidate with the highest value.
+    for trace in &traces {
+        let built: String = trace.steps.iter().map(|s| s.continuation.as_str()).collect();
+        assert!(built.starts_with(&trace.word));
+        for step in &trace.steps {
+            assert!(format!(" {}", step.current).ends_with(&step.pattern));
+            if let Some((draw, low, high)) = step.draw {
+                assert!(low <= draw && draw <= high);
+            }
+        }
+        let chosen = &trace.candidates[trace.chosen];
+        assert_eq!(chosen.word, trace.word);
+        assert!(trace.candidates.iter().all(|c| c.value <= chosen.value));
+        assert!(trace.candidates[..trace.chosen].iter().all(|c| c.value < chosen.value));
+    }
+    assert!(explain_words(&mut Pcg32::seed_from_u64(0), 1, Some(0), &rules).is_err());
+}
+
+#[test]
+fn command_arguments_parsed_once() {
+    // The arguments are parsed by the caller, so reading them never touches the arguments of the test binary.
+    let matches = command::cli().try_get_matches_from(["word_gen", "generate", "-r", "test1.yaml", "-n", "3", "--seed", "7", "--search-path", "./assets/testing"])
+        .expect("Failed to parse arguments.");
+    assert_eq!(command::get_count(&matches), 3);
+    assert_eq!(command::get_seed(&matches), Some(7));
+    assert_eq!(command::get_verify_paths(&matches), None);
+    assert_eq!(command::get_new_rules_output(&matches), (None, false));
+    assert_eq!(command::get_output_format(&matches), OutputFormat::Plain);
+
+    // The words are printed with --format, while --sample-format is the format of the sample text.
+    let formats = command::cli().try_get_matches_from(["word_gen", "-s", "book.epub", "--sample-format", "epub", "--format", "csv"]).unwrap();
+    assert_eq!(command::get_output_format(&formats), OutputFormat::Csv);
+    assert!(command::cli().try_get_matches_from(["word_gen", "-r", "test1.yaml", "-t", "--format", "csv"]).is_err());
+
+    // Blended rules are only saved, and only overwrite a file, when asked to.
+    let blend = command::cli().try_get_matches_from(["word_gen", "blend", "a.yaml", "b.yaml:2"]).expect("Failed to parse arguments.");
+    assert_eq!(command::get_blend(&blend), Some(vec![("a.yaml".to_owned(), 1), ("b.yaml".to_owned(), 2)]));
+    assert_eq!(command::get_new_rules_output(&blend), (None, false));
+    let blend = command::cli().try_get_matches_from(["word_gen", "blend", "a.yaml", "b.yaml", "-o", "c.yaml", "--force"]).unwrap();
+    assert_eq!(command::get_new_rules_output(&blend), (Some("c.yaml".to_owned()), true));
+
+    let resolver = command::resolver(&matches);
+    assert_eq!(command::get_rules(&matches, &resolver).expect("Failed to get rules."), get_rules("test1"));
+    assert_eq!(command::read_rules("test15.yaml", &resolver).expect("Failed to read rules."), get_rules("test15"));
+    assert!(command::read_rules("missing.yaml", &resolver).is_err());
+}
+
+#[test]
+fn write_new_rules_refuses_to_overwrite() {
+    let directory = std::env::temp_dir().join("word_gen_write_new_rules_refuses_to_overwrite");
+    let path = directory.join("rules.yaml");
+    let output = path.to_string_lossy();
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(&path, "kept").unwrap();
+
+    let rules = get_rules("test1");
+    assert!(command::write_new_rules(&rules, "Invented", Some(42), Some(&output), false).is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "kept");
+    command::write_new_rules(&rules, "Invented", Some(42), Some(&output), true).expect("Failed to write rules.");
+    assert!(std::fs::read_to_string(&path).unwrap().starts_with("# Invented by word_gen.\n# seed: 42\n# rng: pcg32 version 1\n"));
+    assert_eq!(command::read_rules(&output, &Resolver::from_environment(&[])).unwrap(), rules);
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn get_family_refuses_to_overwrite() {
+    let directory = std::env::temp_dir().join("word_gen_get_family_refuses_to_overwrite");
+    let output = directory.to_string_lossy().into_owned();
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(directory.join("daughter-2.yaml"), "kept").unwrap();
+
+    let rules = "./assets/examples/example.yaml";
+    let matches = command::cli().try_get_matches_from(["word_gen", "family", "-r", rules, "-d", "2", "-o", &output]).unwrap();
+    let resolver = command::resolver(&matches);
+    assert!(command::get_family(&matches, &resolver, 42).is_err());
+    assert_eq!(std::fs::read_to_string(directory.join("daughter-2.yaml")).unwrap(), "kept");
+    assert!(!directory.join("daughter-1.yaml").exists());
+
+    let matches = command::cli().try_get_matches_from(["word_gen", "family", "-r", rules, "-d", "2", "-o", &output, "--force"]).unwrap();
+    let family = command::get_family(&matches, &resolver, 42).expect("Failed to generate family.").unwrap();
+    let saved = command::read_rules(&directory.join("daughter-2.yaml").to_string_lossy(), &resolver).unwrap();
+    assert_eq!(saved, family.daughters[1].rules);
+    assert!(std::fs::read_to_string(directory.join("daughter-2.yaml")).unwrap().starts_with("# Derived as daughter-2 by word_gen.\n# seed: 42\n"));
+
+    // Without an output the rules are only printed out.
+    let matches = command::cli().try_get_matches_from(["word_gen", "family", "-r", rules, "-d", "2"]).unwrap();
+    assert!(command::get_family(&matches, &resolver, 42).expect("Failed to generate family.").is_some());
+    assert!(!Path::new("./assets/local").exists());
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}